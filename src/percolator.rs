@@ -13,11 +13,12 @@ declare_id!("GFzXiEhiRauw6k59L15zz4UJ9ZANaF5gpPtxEaYCo8jv");
 // 1. mod constants
 pub mod constants {
     use crate::state::{MarketConfig, SlabHeader};
-    use core::mem::{align_of, size_of};
+    use core::mem::{align_of, offset_of, size_of};
     use percolator::RiskEngine;
 
     pub const MAGIC: u64 = 0x504552434f4c4154; // "PERCOLAT"
-    pub const VERSION: u32 = 1;
+    /// Layout version 2 added the wrapper policy block to MarketConfig.
+    pub const VERSION: u32 = 2;
 
     pub const HEADER_LEN: usize = size_of::<SlabHeader>();
     pub const CONFIG_LEN: usize = size_of::<MarketConfig>();
@@ -30,6 +31,13 @@ pub mod constants {
     pub const ENGINE_OFF: usize = align_up(HEADER_LEN + CONFIG_LEN, ENGINE_ALIGN);
     pub const ENGINE_LEN: usize = size_of::<RiskEngine>();
     pub const SLAB_LEN: usize = ENGINE_OFF + ENGINE_LEN;
+
+    /// Version-1 layout (MarketConfig without the policy block). Such slabs
+    /// are rejected until upgraded in place with MigrateSlab.
+    pub const V1_VERSION: u32 = 1;
+    pub const V1_CONFIG_LEN: usize = offset_of!(MarketConfig, min_insurance_to_oi_bps);
+    pub const V1_ENGINE_OFF: usize = align_up(HEADER_LEN + V1_CONFIG_LEN, ENGINE_ALIGN);
+    pub const V1_SLAB_LEN: usize = V1_ENGINE_OFF + ENGINE_LEN;
     pub const MATCHER_ABI_VERSION: u32 = 1;
     pub const MATCHER_CONTEXT_PREFIX_LEN: usize = 64;
     pub const MATCHER_CONTEXT_LEN: usize = 320;
@@ -226,6 +234,909 @@ pub fn compute_inventory_funding_bps_per_slot(
     per_slot
 }
 
+// 1c. Engine read helpers (pure reads for keepers/UIs, no state mutation)
+
+/// Liquidation fee charged for closing `close_size` at `oracle_price`.
+/// Applies params.liquidation_fee_bps capped by params.liquidation_fee_cap,
+/// matching the fee computed inside RiskEngine::liquidate_at_oracle.
+/// The fee actually paid is further limited by the account's capital.
+#[inline]
+pub fn liquidation_fee_for(
+    engine: &percolator::RiskEngine,
+    close_size: i128,
+    oracle_price: u64,
+) -> u128 {
+    verify::liquidation_fee(
+        close_size.unsigned_abs(),
+        oracle_price,
+        engine.params.liquidation_fee_bps,
+        engine.params.liquidation_fee_cap.get(),
+    )
+}
+
+/// Account's contribution to systemic risk at `oracle_price`, for capital charges.
+/// Notional scaled up by the account's share of total_open_interest, so a
+/// position dominating OI counts up to 2x its notional. Flat or unused = 0.
+#[inline]
+pub fn risk_contribution(engine: &percolator::RiskEngine, idx: u16, oracle_price: u64) -> u128 {
+    if (idx as usize) >= percolator::MAX_ACCOUNTS || !engine.is_used(idx as usize) {
+        return 0;
+    }
+    verify::risk_contribution(
+        engine.accounts[idx as usize]
+            .position_size
+            .get()
+            .unsigned_abs(),
+        engine.total_open_interest.get(),
+        oracle_price,
+    )
+}
+
+/// Smallest adverse oracle move (bps) at which the net-skewed book would
+/// exhaust total capital plus insurance, for stress testing.
+/// Skew is the LP net inventory (users' net position is its negation).
+/// Returns None when the book is flat (no directional risk).
+#[inline]
+pub fn insolvency_price_move_bps(
+    engine: &percolator::RiskEngine,
+    current_oracle: u64,
+) -> Option<u32> {
+    let buffer = engine
+        .c_tot
+        .get()
+        .saturating_add(engine.insurance_fund.balance.get());
+    verify::insolvency_move_bps(
+        compute_net_lp_pos(engine).unsigned_abs(),
+        current_oracle,
+        buffer,
+    )
+}
+
+/// LP inventory (signed position_size) for delta hedging.
+/// Returns None if lp_idx is not an in-use LP account.
+#[inline]
+pub fn lp_inventory(engine: &percolator::RiskEngine, lp_idx: u16) -> Option<i128> {
+    let i = lp_idx as usize;
+    if i >= percolator::MAX_ACCOUNTS || !engine.is_used(i) || !engine.accounts[i].is_lp() {
+        return None;
+    }
+    Some(engine.accounts[i].position_size.get())
+}
+
+/// Enumerate (lp_idx, inventory) for all LPs into `out`, in index order.
+/// Returns the number of entries written (stops early if `out` is full). O(n).
+pub fn all_lp_inventory(engine: &percolator::RiskEngine, out: &mut [(u16, i128)]) -> usize {
+    let mut n = 0;
+    for i in 0..percolator::MAX_ACCOUNTS {
+        if n == out.len() {
+            break;
+        }
+        if let Some(inv) = lp_inventory(engine, i as u16) {
+            out[n] = (i as u16, inv);
+            n += 1;
+        }
+    }
+    n
+}
+
+/// Index of the first used account whose `account_id` matches. O(n) scan;
+/// duplicates (not produced by the engine) resolve to the lowest index.
+pub fn find_by_account_id(engine: &percolator::RiskEngine, id: u64) -> Option<u16> {
+    (0..percolator::MAX_ACCOUNTS)
+        .find(|&i| engine.is_used(i) && engine.accounts[i].account_id == id)
+        .map(|i| i as u16)
+}
+
+/// Write used indices of the given kind into `out` (index order). Returns the
+/// count written (stops when full). Users and LPs partition the used set. O(n).
+pub fn accounts_by_kind(
+    engine: &percolator::RiskEngine,
+    kind: percolator::AccountKind,
+    out: &mut [u16],
+) -> usize {
+    let want_lp = !matches!(kind, percolator::AccountKind::User);
+    let mut n = 0;
+    for i in 0..percolator::MAX_ACCOUNTS {
+        if n == out.len() {
+            break;
+        }
+        if engine.is_used(i) && engine.accounts[i].is_lp() == want_lp {
+            out[n] = i as u16;
+            n += 1;
+        }
+    }
+    n
+}
+
+/// Number of used accounts (users and LPs) owned by `owner`. O(n).
+pub fn owner_account_count(engine: &percolator::RiskEngine, owner: &[u8; 32]) -> u64 {
+    (0..percolator::MAX_ACCOUNTS)
+        .filter(|&i| engine.is_used(i) && engine.accounts[i].owner == *owner)
+        .count() as u64
+}
+
+/// Signed sum of position_size over used accounts. None on overflow. O(n).
+pub fn position_sum(engine: &percolator::RiskEngine) -> Option<i128> {
+    let mut sum: i128 = 0;
+    for i in 0..percolator::MAX_ACCOUNTS {
+        if engine.is_used(i) {
+            sum = sum.checked_add(engine.accounts[i].position_size.get())?;
+        }
+    }
+    Some(sum)
+}
+
+/// True iff positions net to exactly zero. Every fill applies +delta to the
+/// user and -delta to the LP, so trades preserve it; an oracle liquidation
+/// closes a position with no counterparty fill, leaving a remnant that
+/// `position_sum` reports. O(n).
+pub fn positions_zero_sum(engine: &percolator::RiskEngine) -> bool {
+    position_sum(engine) == Some(0)
+}
+
+/// Drift of the engine's stored aggregates from their account sums, as
+/// recomputed minus stored. All-zero on a healthy slab.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AggregateDelta {
+    pub c_tot: i128,
+    pub pnl_pos_tot: i128,
+    pub total_open_interest: i128,
+}
+
+/// Recompute c_tot, pnl_pos_tot and total_open_interest (sum of
+/// |position_size|) from used accounts and report the drift, so operators
+/// can alarm on nonzero deltas. Pure read; repairing the stored values is
+/// the engine's job. O(n).
+pub fn aggregate_drift(engine: &percolator::RiskEngine) -> AggregateDelta {
+    let (mut c_tot, mut pnl_pos_tot, mut oi) = (0u128, 0u128, 0u128);
+    for i in 0..percolator::MAX_ACCOUNTS {
+        if engine.is_used(i) {
+            let acc = &engine.accounts[i];
+            c_tot = c_tot.saturating_add(acc.capital.get());
+            pnl_pos_tot = pnl_pos_tot.saturating_add(acc.pnl.get().max(0) as u128);
+            oi = oi.saturating_add(acc.position_size.get().unsigned_abs());
+        }
+    }
+    let delta =
+        |recomputed: u128, stored: u128| (recomputed as i128).saturating_sub(stored as i128);
+    AggregateDelta {
+        c_tot: delta(c_tot, engine.c_tot.get()),
+        pnl_pos_tot: delta(pnl_pos_tot, engine.pnl_pos_tot.get()),
+        total_open_interest: delta(oi, engine.total_open_interest.get()),
+    }
+}
+
+/// Headline TVL: the vault balance, which backs all capital, insurance and
+/// PnL. O(1).
+#[inline]
+pub fn tvl(engine: &percolator::RiskEngine) -> u128 {
+    engine.vault.get()
+}
+
+/// Capital by account kind plus insurance. user_capital + lp_capital equals
+/// c_tot on a consistent slab.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TvlBreakdown {
+    pub user_capital: u128,
+    pub lp_capital: u128,
+    pub insurance: u128,
+}
+
+/// Split capital across users and LPs for dashboards. O(n).
+pub fn tvl_breakdown(engine: &percolator::RiskEngine) -> TvlBreakdown {
+    let mut b = TvlBreakdown {
+        insurance: engine.insurance_fund.balance.get(),
+        ..TvlBreakdown::default()
+    };
+    for i in 0..percolator::MAX_ACCOUNTS {
+        if !engine.is_used(i) {
+            continue;
+        }
+        let acc = &engine.accounts[i];
+        if acc.is_lp() {
+            b.lp_capital = b.lp_capital.saturating_add(acc.capital.get());
+        } else {
+            b.user_capital = b.user_capital.saturating_add(acc.capital.get());
+        }
+    }
+    b
+}
+
+/// Trial balance for double-entry reconciliation.
+/// By construction: vault == c_tot + insurance + residual.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TrialBalance {
+    pub vault: u128,
+    pub c_tot: u128,
+    pub insurance: u128,
+    pub pnl_pos_tot: u128,
+    /// Summed magnitude of negative PnL across used accounts
+    pub pnl_neg_abs_tot: u128,
+    /// vault - c_tot - insurance (negative means under-backed capital)
+    pub residual: i128,
+}
+
+/// Build the trial balance from engine aggregates plus one O(n) scan for
+/// negative PnL (the engine only maintains the positive aggregate).
+pub fn trial_balance(engine: &percolator::RiskEngine) -> TrialBalance {
+    let vault = engine.vault.get();
+    let c_tot = engine.c_tot.get();
+    let insurance = engine.insurance_fund.balance.get();
+    let mut pnl_neg_abs_tot: u128 = 0;
+    for i in 0..percolator::MAX_ACCOUNTS {
+        if engine.is_used(i) {
+            let pnl = engine.accounts[i].pnl.get();
+            if pnl < 0 {
+                pnl_neg_abs_tot = pnl_neg_abs_tot.saturating_add(pnl.unsigned_abs());
+            }
+        }
+    }
+    TrialBalance {
+        vault,
+        c_tot,
+        insurance,
+        pnl_pos_tot: engine.pnl_pos_tot.get(),
+        pnl_neg_abs_tot,
+        residual: verify::trial_balance_residual(vault, c_tot, insurance),
+    }
+}
+
+/// Haircut a profitable withdrawal of `pnl_amount` would incur at the current
+/// ratio: pnl_amount - effective_pos_pnl(pnl_amount). 0 when fully backed.
+#[inline]
+pub fn haircut_on_pnl(engine: &percolator::RiskEngine, pnl_amount: u128) -> u128 {
+    let pnl = core::cmp::min(pnl_amount, i128::MAX as u128) as i128;
+    pnl_amount.saturating_sub(engine.effective_pos_pnl(pnl))
+}
+
+/// Total users can claim at `now_slot`: sum over used accounts of capital
+/// plus the haircut warmed part of positive PnL (net of reserved). PnL still
+/// warming up is not yet withdrawable and is left out. Never exceeds
+/// vault - insurance while conservation holds. O(n).
+pub fn total_user_claims(engine: &percolator::RiskEngine, now_slot: u64) -> u128 {
+    let mut total = 0u128;
+    for i in 0..percolator::MAX_ACCOUNTS {
+        if !engine.is_used(i) {
+            continue;
+        }
+        let acc = &engine.accounts[i];
+        let pnl = acc.pnl.get();
+        let avail = if pnl > 0 {
+            (pnl as u128).saturating_sub(acc.reserved_pnl as u128)
+        } else {
+            0
+        };
+        let warmed = verify::warmed_pnl(
+            avail,
+            acc.warmup_slope_per_step.get(),
+            now_slot.saturating_sub(acc.warmup_started_at_slot),
+        );
+        total = total
+            .saturating_add(acc.capital.get())
+            .saturating_add(engine.effective_pos_pnl(warmed as i128));
+    }
+    total
+}
+
+/// Projected slots until insurance above the risk-reduction threshold is
+/// exhausted at `avg_loss_per_slot`. u64::MAX for a zero loss rate.
+#[inline]
+pub fn insurance_runway_slots(engine: &percolator::RiskEngine, avg_loss_per_slot: u128) -> u64 {
+    let spendable = engine
+        .insurance_fund
+        .balance
+        .get()
+        .saturating_sub(engine.risk_reduction_threshold());
+    verify::insurance_runway_slots(spendable, avg_loss_per_slot)
+}
+
+/// Total maintenance fees owed to the system: sum of |fee_credits| over used
+/// accounts with negative fee_credits. O(n).
+pub fn total_fee_debt(engine: &percolator::RiskEngine) -> u128 {
+    (0..percolator::MAX_ACCOUNTS)
+        .filter(|&i| engine.is_used(i))
+        .map(|i| engine.accounts[i].fee_credits.get())
+        .filter(|&c| c < 0)
+        .fold(0u128, |acc, c| acc.saturating_add(c.unsigned_abs()))
+}
+
+/// Total prepaid fee credit: sum of positive fee_credits over used accounts. O(n).
+pub fn total_fee_credit(engine: &percolator::RiskEngine) -> u128 {
+    (0..percolator::MAX_ACCOUNTS)
+        .filter(|&i| engine.is_used(i))
+        .map(|i| engine.accounts[i].fee_credits.get())
+        .filter(|&c| c > 0)
+        .fold(0u128, |acc, c| acc.saturating_add(c as u128))
+}
+
+/// Zombie: open position with zero MTM equity at `oracle_price` (capital
+/// fully consumed by losses, awaiting liquidation). False for unused slots.
+pub fn is_zombie(engine: &percolator::RiskEngine, idx: u16, oracle_price: u64) -> bool {
+    let i = idx as usize;
+    if i >= percolator::MAX_ACCOUNTS || !engine.is_used(i) {
+        return false;
+    }
+    let acc = &engine.accounts[i];
+    verify::is_zombie(
+        acc.capital.get(),
+        acc.pnl.get(),
+        acc.position_size.get(),
+        acc.entry_price,
+        oracle_price,
+    )
+}
+
+/// Write indices of zombie accounts at `oracle_price` into `out`, scanning
+/// `window` slots from `start` and wrapping at MAX_ACCOUNTS. Returns the
+/// count written (stops when full). KeeperCrank runs this over the window
+/// just ahead of its sweep cursor. O(window).
+pub fn zombie_accounts(
+    engine: &percolator::RiskEngine,
+    oracle_price: u64,
+    start: u16,
+    window: u16,
+    out: &mut [u16],
+) -> usize {
+    let window = core::cmp::min(window as usize, percolator::MAX_ACCOUNTS);
+    let mut n = 0;
+    for k in 0..window {
+        if n == out.len() {
+            break;
+        }
+        let i = (start as usize + k) % percolator::MAX_ACCOUNTS;
+        if is_zombie(engine, i as u16, oracle_price) {
+            out[n] = i as u16;
+            n += 1;
+        }
+    }
+    n
+}
+
+/// Write indices of accounts with an open position at or below maintenance
+/// margin at `oracle_price` into `out`, zombies first, then the rest in index
+/// order. Returns the count written (stops when full). Read-only: keepers use
+/// it to prioritize liquidations. O(n).
+pub fn accounts_below_maintenance(
+    engine: &percolator::RiskEngine,
+    oracle_price: u64,
+    out: &mut [u16],
+) -> usize {
+    let mm_bps = engine.params.maintenance_margin_bps;
+    let mut n = 0;
+    for zombies_pass in [true, false] {
+        for i in 0..percolator::MAX_ACCOUNTS {
+            if n == out.len() {
+                return n;
+            }
+            if !engine.is_used(i) || is_zombie(engine, i as u16, oracle_price) != zombies_pass {
+                continue;
+            }
+            let acc = &engine.accounts[i];
+            if verify::below_margin_mtm(
+                acc.capital.get(),
+                acc.pnl.get(),
+                acc.position_size.get(),
+                acc.entry_price,
+                oracle_price,
+                mm_bps,
+            ) {
+                out[n] = i as u16;
+                n += 1;
+            }
+        }
+    }
+    n
+}
+
+/// Write indices of accounts with an open position whose entry_price deviates
+/// from `oracle_price` by more than `threshold_bps` into `out` (index order).
+/// Returns the count written (stops when full). Keepers can mark-settle them. O(n).
+pub fn stale_mark_accounts(
+    engine: &percolator::RiskEngine,
+    oracle_price: u64,
+    threshold_bps: u16,
+    out: &mut [u16],
+) -> usize {
+    let mut n = 0;
+    for i in 0..percolator::MAX_ACCOUNTS {
+        if n == out.len() {
+            break;
+        }
+        if !engine.is_used(i) {
+            continue;
+        }
+        let acc = &engine.accounts[i];
+        if !acc.position_size.is_zero()
+            && verify::entry_deviation_exceeds(acc.entry_price, oracle_price, threshold_bps)
+        {
+            out[n] = i as u16;
+            n += 1;
+        }
+    }
+    n
+}
+
+/// Vault needed to honor every close and withdrawal at `oracle_price`:
+/// c_tot + insurance + sum of max(0, pnl + mark_pnl) over used accounts.
+/// Losses are not netted against gains, so this is conservative. Mark
+/// overflow counts the stored PnL only. O(n).
+pub fn required_vault(engine: &percolator::RiskEngine, oracle_price: u64) -> u128 {
+    let mut total = engine
+        .c_tot
+        .get()
+        .saturating_add(engine.insurance_fund.balance.get());
+    for i in 0..percolator::MAX_ACCOUNTS {
+        if !engine.is_used(i) {
+            continue;
+        }
+        let acc = &engine.accounts[i];
+        let mark =
+            verify::mark_pnl(acc.position_size.get(), acc.entry_price, oracle_price).unwrap_or(0);
+        total = total.saturating_add(verify::pnl_claim_at_mark(acc.pnl.get(), mark));
+    }
+    total
+}
+
+/// Fill `out` with margin health for each valid, used index in `indices`
+/// (invalid ones are skipped). Returns the count written (stops when full).
+pub fn margin_health_batch(
+    engine: &percolator::RiskEngine,
+    indices: &[u16],
+    oracle_price: u64,
+    out: &mut [verify::MarginHealth],
+) -> usize {
+    let mut n = 0;
+    for &idx in indices {
+        if n == out.len() {
+            break;
+        }
+        let i = idx as usize;
+        if i >= percolator::MAX_ACCOUNTS || !engine.is_used(i) {
+            continue;
+        }
+        let acc = &engine.accounts[i];
+        out[n] = verify::margin_health(
+            acc.capital.get(),
+            acc.pnl.get(),
+            acc.position_size.get(),
+            acc.entry_price,
+            oracle_price,
+            engine.params.initial_margin_bps,
+            engine.params.maintenance_margin_bps,
+        );
+        n += 1;
+    }
+    n
+}
+
+/// Aggregate impact of liquidating every unsafe account at a shocked price.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CascadeReport {
+    pub accounts_liquidated: u32,
+    /// Bankruptcy deficits covered by insurance (capped at its balance)
+    pub insurance_consumed: u128,
+    /// Deficits beyond insurance, absorbed by the haircut on positive PnL
+    pub socialized_loss: u128,
+}
+
+/// Stress read: liquidate (on paper) every account below maintenance at
+/// `shock_oracle`. Liquidation is at the oracle and does not move it, so one
+/// pass captures the cascade. Ignores liquidation fees. Pure read. O(n).
+pub fn simulate_cascade(engine: &percolator::RiskEngine, shock_oracle: u64) -> CascadeReport {
+    let mm_bps = engine.params.maintenance_margin_bps;
+    let mut liquidated = 0u32;
+    let mut deficit = 0u128;
+    for i in 0..percolator::MAX_ACCOUNTS {
+        if !engine.is_used(i) {
+            continue;
+        }
+        let acc = &engine.accounts[i];
+        let (capital, pnl, pos) = (acc.capital.get(), acc.pnl.get(), acc.position_size.get());
+        if verify::below_margin_mtm(capital, pnl, pos, acc.entry_price, shock_oracle, mm_bps) {
+            liquidated += 1;
+            deficit = deficit.saturating_add(verify::account_deficit(
+                capital,
+                pnl,
+                pos,
+                acc.entry_price,
+                shock_oracle,
+            ));
+        }
+    }
+    let insurance_consumed = core::cmp::min(deficit, engine.insurance_fund.balance.get());
+    CascadeReport {
+        accounts_liquidated: liquidated,
+        insurance_consumed,
+        socialized_loss: deficit - insurance_consumed,
+    }
+}
+
+/// Per-account stress indicator: true iff account `idx` stays strictly above
+/// maintenance margin at `shock_oracle`. Flat accounts always survive.
+/// False for unused accounts. Pure read.
+pub fn survives_shock(engine: &percolator::RiskEngine, idx: u16, shock_oracle: u64) -> bool {
+    let i = idx as usize;
+    if i >= percolator::MAX_ACCOUNTS || !engine.is_used(i) {
+        return false;
+    }
+    let acc = &engine.accounts[i];
+    !verify::below_margin_mtm(
+        acc.capital.get(),
+        acc.pnl.get(),
+        acc.position_size.get(),
+        acc.entry_price,
+        shock_oracle,
+        engine.params.maintenance_margin_bps,
+    )
+}
+
+/// Early warning for unrecoverable accounts: open positions whose MTM loss
+/// at `oracle_price` exceeds their capital, i.e. what liquidation would draw
+/// from insurance or socialize. Writes (idx, deficit) into `out` (index
+/// order) and returns the count written (stops when full). Pure read. O(n).
+pub fn underwater_accounts(
+    engine: &percolator::RiskEngine,
+    oracle_price: u64,
+    out: &mut [(u16, u128)],
+) -> usize {
+    let mut n = 0;
+    for i in 0..percolator::MAX_ACCOUNTS {
+        if n == out.len() {
+            break;
+        }
+        if !engine.is_used(i) {
+            continue;
+        }
+        let acc = &engine.accounts[i];
+        let pos = acc.position_size.get();
+        if pos == 0 {
+            continue;
+        }
+        let deficit = verify::account_deficit(
+            acc.capital.get(),
+            acc.pnl.get(),
+            pos,
+            acc.entry_price,
+            oracle_price,
+        );
+        if deficit > 0 {
+            out[n] = (i as u16, deficit);
+            n += 1;
+        }
+    }
+    n
+}
+
+/// Effect of a proposed maintenance_margin_bps change at `oracle_price`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MarginChangeImpact {
+    /// Accounts safe today that the new requirement would make liquidatable
+    pub newly_liquidatable: u32,
+    /// Summed notional of those accounts' positions
+    pub total_liquidation_notional: u128,
+}
+
+/// Governance preview: which accounts a new maintenance requirement would
+/// push under. Evaluated against the current parameters without mutating
+/// them. Pure read. O(n).
+pub fn margin_change_impact(
+    engine: &percolator::RiskEngine,
+    new_maintenance_bps: u16,
+    oracle_price: u64,
+) -> MarginChangeImpact {
+    let mut impact = MarginChangeImpact::default();
+    for i in 0..percolator::MAX_ACCOUNTS {
+        if !engine.is_used(i) {
+            continue;
+        }
+        let acc = &engine.accounts[i];
+        let pos = acc.position_size.get();
+        if verify::newly_liquidatable(
+            acc.capital.get(),
+            acc.pnl.get(),
+            pos,
+            acc.entry_price,
+            oracle_price,
+            engine.params.maintenance_margin_bps,
+            new_maintenance_bps as u64,
+        ) {
+            impact.newly_liquidatable += 1;
+            impact.total_liquidation_notional = impact
+                .total_liquidation_notional
+                .saturating_add(verify::position_notional(pos, oracle_price));
+        }
+    }
+    impact
+}
+
+/// Size-weighted average entry across the account's exposure. With a single
+/// market this is `entry_price`; None for flat or unused accounts.
+pub fn average_entry(engine: &percolator::RiskEngine, idx: u16) -> Option<u64> {
+    let i = idx as usize;
+    if i >= percolator::MAX_ACCOUNTS || !engine.is_used(i) {
+        return None;
+    }
+    let acc = &engine.accounts[i];
+    if acc.position_size.get() == 0 {
+        return None;
+    }
+    Some(acc.entry_price)
+}
+
+/// What each used account would receive if every position closed at
+/// `oracle_price` and the vault paid out: capital first, then haircut profit.
+/// Writes (idx, payout) into `out` (index order) and returns the count written
+/// (stops when full). The payouts sum to at most `vault`. Pure read. O(n).
+pub fn wind_down_payouts(
+    engine: &percolator::RiskEngine,
+    oracle_price: u64,
+    out: &mut [(u16, u128)],
+) -> usize {
+    let closed = |i: usize| {
+        let acc = &engine.accounts[i];
+        let mark =
+            verify::mark_pnl(acc.position_size.get(), acc.entry_price, oracle_price).unwrap_or(0);
+        verify::close_at_mark(acc.capital.get(), acc.pnl.get(), mark)
+    };
+    let (mut c_tot, mut profit_tot) = (0u128, 0u128);
+    for i in 0..percolator::MAX_ACCOUNTS {
+        if engine.is_used(i) {
+            let (capital, profit) = closed(i);
+            c_tot = c_tot.saturating_add(capital);
+            profit_tot = profit_tot.saturating_add(profit);
+        }
+    }
+    let (vault, insurance) = (engine.vault.get(), engine.insurance_fund.balance.get());
+    let mut n = 0;
+    for i in 0..percolator::MAX_ACCOUNTS {
+        if n == out.len() {
+            break;
+        }
+        if !engine.is_used(i) {
+            continue;
+        }
+        let (capital, profit) = closed(i);
+        out[n] = (
+            i as u16,
+            verify::wind_down_payout(capital, profit, c_tot, profit_tot, vault, insurance),
+        );
+        n += 1;
+    }
+    n
+}
+
+/// Slots from `now_slot` until account `idx`'s positive PnL (net of
+/// reserved) is fully warmed. 0 when nothing is pending, u64::MAX when the
+/// slope is zero with PnL pending or the account is unused.
+pub fn slots_to_full_warmup(engine: &percolator::RiskEngine, idx: u16, now_slot: u64) -> u64 {
+    let i = idx as usize;
+    if i >= percolator::MAX_ACCOUNTS || !engine.is_used(i) {
+        return u64::MAX;
+    }
+    let acc = &engine.accounts[i];
+    let pnl = acc.pnl.get();
+    let avail = if pnl > 0 {
+        (pnl as u128).saturating_sub(acc.reserved_pnl as u128)
+    } else {
+        0
+    };
+    verify::slots_to_full_warmup(
+        avail,
+        acc.warmup_slope_per_step.get(),
+        now_slot.saturating_sub(acc.warmup_started_at_slot),
+    )
+}
+
+/// Keeper planning: max slots between cranks so price (moving up to
+/// `volatility_bps` per slot) can't exceed `buffer_bps` before the next
+/// liquidation check. Bounded by max_crank_staleness_slots.
+pub fn recommended_crank_interval(
+    engine: &percolator::RiskEngine,
+    volatility_bps: u16,
+    buffer_bps: u16,
+) -> u64 {
+    verify::crank_interval(
+        volatility_bps,
+        buffer_bps,
+        engine.params.max_crank_staleness_slots,
+    )
+}
+
+/// Concentration risk: the largest used account's |position_size| as a
+/// share of total_open_interest in bps. 0 for an empty book. O(n).
+pub fn oi_concentration_bps(engine: &percolator::RiskEngine) -> u16 {
+    let max_abs = (0..percolator::MAX_ACCOUNTS)
+        .filter(|&i| engine.is_used(i))
+        .map(|i| engine.accounts[i].position_size.get().unsigned_abs())
+        .max()
+        .unwrap_or(0);
+    verify::concentration_bps(max_abs, engine.total_open_interest.get())
+}
+
+/// Keeper crank sweep progress, for orchestrators running multiple keepers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SweepProgress {
+    /// Next account index the crank will visit
+    pub cursor: u16,
+    pub total_used: u16,
+    /// Slot at which the current full sweep started
+    pub started_slot: u64,
+    /// Slot at which the last full sweep completed
+    pub last_completed_slot: u64,
+}
+
+/// Snapshot the crank's sweep progress from engine fields. O(1).
+#[inline]
+pub fn sweep_progress(engine: &percolator::RiskEngine) -> SweepProgress {
+    SweepProgress {
+        cursor: engine.crank_cursor,
+        total_used: engine.num_used_accounts,
+        started_slot: engine.last_full_sweep_start_slot,
+        last_completed_slot: engine.last_full_sweep_completed_slot,
+    }
+}
+
+/// Maintenance-margin liquidation price for account `idx`: the oracle price
+/// at which it is at (not above) maintenance margin while one tick safer it
+/// is above. None for flat/unused accounts or when no such boundary exists
+/// in [1, MAX_ORACLE_PRICE].
+pub fn liquidation_price(engine: &percolator::RiskEngine, idx: u16) -> Option<u64> {
+    let i = idx as usize;
+    if i >= percolator::MAX_ACCOUNTS || !engine.is_used(i) {
+        return None;
+    }
+    let acc = &engine.accounts[i];
+    verify::liquidation_price(
+        acc.capital.get(),
+        acc.pnl.get(),
+        acc.position_size.get(),
+        acc.entry_price,
+        engine.params.maintenance_margin_bps,
+        percolator::MAX_ORACLE_PRICE,
+    )
+}
+
+/// Largest |size| a fresh account funded with `collateral` can open at
+/// `oracle_price` under initial margin, after the trading fee.
+pub fn max_position_for_collateral(
+    engine: &percolator::RiskEngine,
+    collateral: u128,
+    oracle_price: u64,
+) -> i128 {
+    verify::max_position_for_collateral(
+        collateral,
+        oracle_price,
+        engine.params.initial_margin_bps,
+        engine.params.trading_fee_bps,
+        i128::MAX as u128,
+    ) as i128
+}
+
+/// Additional notional account `idx` can open at `oracle_price`: its free
+/// equity (MTM equity minus the current position's initial margin) run
+/// through the same search as `max_position_for_collateral`, fee included.
+/// 0 for unused accounts. Pure read.
+pub fn buying_power(engine: &percolator::RiskEngine, idx: u16, oracle_price: u64) -> u128 {
+    let i = idx as usize;
+    if i >= percolator::MAX_ACCOUNTS || !engine.is_used(i) {
+        return 0;
+    }
+    let acc = &engine.accounts[i];
+    let health = verify::margin_health(
+        acc.capital.get(),
+        acc.pnl.get(),
+        acc.position_size.get(),
+        acc.entry_price,
+        oracle_price,
+        engine.params.initial_margin_bps,
+        engine.params.maintenance_margin_bps,
+    );
+    let free = verify::free_equity(health.equity, health.initial_required);
+    let size = verify::max_position_for_collateral(
+        free,
+        oracle_price,
+        engine.params.initial_margin_bps,
+        engine.params.trading_fee_bps,
+        i128::MAX as u128,
+    );
+    verify::position_notional(size as i128, oracle_price)
+}
+
+/// Ongoing cost of holding account `idx`'s position for one slot at the
+/// given funding rate: maintenance fee plus funding paid (negative = earns
+/// carry). 0 for unused accounts. Pure read.
+pub fn cost_of_carry_per_slot(
+    engine: &percolator::RiskEngine,
+    idx: u16,
+    current_funding_rate_bps: i64,
+    oracle_price: u64,
+) -> i128 {
+    let i = idx as usize;
+    if i >= percolator::MAX_ACCOUNTS || !engine.is_used(i) {
+        return 0;
+    }
+    verify::carry_per_slot(
+        engine.params.maintenance_fee_per_slot.get(),
+        engine.accounts[i].position_size.get(),
+        oracle_price,
+        current_funding_rate_bps,
+    )
+}
+
+/// Taker fee bps account `idx` currently pays. This engine has a single flat
+/// `trading_fee_bps` (no tiers or discounts), so every used account pays it.
+/// None for unused accounts.
+pub fn effective_fee_bps(engine: &percolator::RiskEngine, idx: u16) -> Option<u64> {
+    let i = idx as usize;
+    if i >= percolator::MAX_ACCOUNTS || !engine.is_used(i) {
+        return None;
+    }
+    Some(engine.params.trading_fee_bps)
+}
+
+/// Fee account `idx` would pay to trade `size` at `oracle_price`:
+/// ceil(notional * effective_fee_bps / 10_000). None for unused accounts.
+pub fn estimate_trade_fee(
+    engine: &percolator::RiskEngine,
+    idx: u16,
+    size: i128,
+    oracle_price: u64,
+) -> Option<u128> {
+    let bps = effective_fee_bps(engine, idx)?;
+    Some(verify::trade_fee(size.unsigned_abs(), oracle_price, bps))
+}
+
+/// Vault surplus not owed to capital, insurance, or positive PnL:
+/// max(0, vault - c_tot - insurance - pnl_pos_tot). Nonzero means drift.
+#[inline]
+pub fn orphaned_vault(engine: &percolator::RiskEngine) -> u128 {
+    verify::orphaned_vault(
+        engine.vault.get(),
+        engine.c_tot.get(),
+        engine.insurance_fund.balance.get(),
+        engine.pnl_pos_tot.get(),
+    )
+}
+
+/// Whether paying `amount` (units) of capital out of account `idx` keeps the
+/// system honorable: the payout fits in the account's capital once its
+/// negative PnL is settled, the vault still backs every other account's
+/// capital plus insurance after it leaves, and insurance is above the
+/// risk-reduction threshold. Per-account margin checks are left to the
+/// engine's `withdraw`.
+pub fn system_can_honor_withdrawal(
+    engine: &percolator::RiskEngine,
+    idx: u16,
+    amount: u128,
+) -> bool {
+    let i = idx as usize;
+    if i >= percolator::MAX_ACCOUNTS || !engine.is_used(i) {
+        return false;
+    }
+    verify::withdrawal_honorable(
+        engine.vault.get(),
+        engine.c_tot.get(),
+        engine.insurance_fund.balance.get(),
+        engine.accounts[i].capital.get(),
+        engine.accounts[i].pnl.get(),
+        amount,
+        engine.risk_reduction_threshold(),
+    )
+}
+
+// 1d. Engine mutation helpers (called from admin handlers only)
+
+/// Credit any orphaned vault surplus to the insurance fund. Returns the amount swept.
+/// Vault is unchanged, so conservation (vault >= c_tot + insurance) still holds.
+pub fn sweep_orphaned_to_insurance(engine: &mut percolator::RiskEngine) -> u128 {
+    let orphan = orphaned_vault(engine);
+    if orphan > 0 {
+        let bal = engine.insurance_fund.balance.get();
+        engine.insurance_fund.balance = percolator::U128::new(bal.saturating_add(orphan));
+    }
+    orphan
+}
+
 // =============================================================================
 // Pure helpers for Kani verification (program-level invariants only)
 // =============================================================================
@@ -821,57 +1732,687 @@ pub mod verify {
     pub fn init_market_scale_ok(unit_scale: u32) -> bool {
         unit_scale <= crate::constants::MAX_UNIT_SCALE
     }
-}
 
-// 2. mod zc (Zero-Copy unsafe island)
-#[allow(unsafe_code)]
-pub mod zc {
-    use crate::constants::{ENGINE_ALIGN, ENGINE_LEN, ENGINE_OFF};
-    use core::mem::offset_of;
-    use percolator::RiskEngine;
-    use solana_program::program_error::ProgramError;
+    // =========================================================================
+    // Liquidation fee math (pure logic)
+    // =========================================================================
 
-    // Use const to export the actual offset for debugging
-    pub const ACCOUNTS_OFFSET: usize = offset_of!(RiskEngine, accounts);
+    /// Liquidation fee: ceil(notional * fee_bps / 10_000), capped at fee_cap,
+    /// where notional = close_abs * price_e6 / 1e6.
+    /// Zero notional or zero bps charges nothing (same as the engine).
+    #[inline]
+    pub fn liquidation_fee(close_abs: u128, price_e6: u64, fee_bps: u64, fee_cap: u128) -> u128 {
+        let notional = close_abs.saturating_mul(price_e6 as u128) / 1_000_000;
+        let fee_raw = if notional > 0 && fee_bps > 0 {
+            notional
+                .saturating_mul(fee_bps as u128)
+                .saturating_add(9_999)
+                / 10_000
+        } else {
+            0
+        };
+        core::cmp::min(fee_raw, fee_cap)
+    }
 
-    /// Old slab length (before Account struct reordering migration)
-    /// Old slabs support up to 4095 accounts, new slabs support 4096.
-    const OLD_ENGINE_LEN: usize = ENGINE_LEN - 8;
+    // =========================================================================
+    // Position entry price validation (pure logic)
+    // =========================================================================
 
+    /// A nonzero position must have entry_price in (0, max_price].
+    /// Flat positions carry no entry requirement.
     #[inline]
-    pub fn engine_ref<'a>(data: &'a [u8]) -> Result<&'a RiskEngine, ProgramError> {
-        // Accept old slabs (ENGINE_LEN - 8) for backward compatibility
-        if data.len() < ENGINE_OFF + OLD_ENGINE_LEN {
-            return Err(ProgramError::InvalidAccountData);
-        }
-        let ptr = unsafe { data.as_ptr().add(ENGINE_OFF) };
-        if (ptr as usize) % ENGINE_ALIGN != 0 {
-            return Err(ProgramError::InvalidAccountData);
-        }
-        Ok(unsafe { &*(ptr as *const RiskEngine) })
+    pub fn entry_price_ok(position_size: i128, entry_price: u64, max_price: u64) -> bool {
+        position_size == 0 || (entry_price > 0 && entry_price <= max_price)
     }
 
+    // =========================================================================
+    // Risk contribution math (pure logic)
+    // =========================================================================
+
+    /// Risk contribution: notional * (1 + share), share = abs_pos / total_oi
+    /// clamped to [0, 1] in bps. notional = abs_pos * price_e6 / 1e6.
+    /// Bounded by 2 * notional; zero for a flat position.
     #[inline]
-    pub fn engine_mut<'a>(data: &'a mut [u8]) -> Result<&'a mut RiskEngine, ProgramError> {
-        // Accept old slabs (ENGINE_LEN - 8) for backward compatibility
-        if data.len() < ENGINE_OFF + OLD_ENGINE_LEN {
-            return Err(ProgramError::InvalidAccountData);
+    pub fn risk_contribution(abs_pos: u128, total_oi: u128, price_e6: u64) -> u128 {
+        let notional = abs_pos.saturating_mul(price_e6 as u128) / 1_000_000;
+        if notional == 0 || total_oi == 0 {
+            return notional;
         }
-        let ptr = unsafe { data.as_mut_ptr().add(ENGINE_OFF) };
-        if (ptr as usize) % ENGINE_ALIGN != 0 {
-            return Err(ProgramError::InvalidAccountData);
+        let share_bps = core::cmp::min(abs_pos.saturating_mul(10_000) / total_oi, 10_000);
+        notional.saturating_add(notional.saturating_mul(share_bps) / 10_000)
+    }
+
+    // =========================================================================
+    // Insurance ratio withdrawal gate (pure logic)
+    // =========================================================================
+
+    /// Capital withdrawals are blocked iff the gate is enabled (min_bps > 0),
+    /// there is open interest, and insurance * 10_000 / total_oi < min_bps.
+    #[inline]
+    pub fn insurance_ratio_blocks_withdraw(insurance: u128, total_oi: u128, min_bps: u64) -> bool {
+        if min_bps == 0 || total_oi == 0 {
+            return false;
         }
-        Ok(unsafe { &mut *(ptr as *mut RiskEngine) })
+        insurance.saturating_mul(10_000) / total_oi < min_bps as u128
     }
 
-    // NOTE: engine_write was removed because it requires passing RiskEngine by value,
-    // which stack-allocates the ~6MB struct and causes stack overflow in BPF.
-    // Use engine_mut() + init_in_place() instead for initialization.
+    // =========================================================================
+    // Insolvency stress move (pure logic)
+    // =========================================================================
 
-    use solana_program::{
-        account_info::AccountInfo, instruction::Instruction as SolInstruction,
-        program::invoke_signed,
-    };
+    /// Adverse move in bps that turns the net skew's loss into `buffer`:
+    /// move_bps = ceil(buffer * 10_000 * 1e6 / (net_abs * price_e6)),
+    /// saturating at u32::MAX. None when net_abs == 0 (flat book) or price == 0.
+    #[inline]
+    pub fn insolvency_move_bps(net_abs: u128, price_e6: u64, buffer: u128) -> Option<u32> {
+        if net_abs == 0 || price_e6 == 0 {
+            return None;
+        }
+        // Loss per bps of move = net_abs * price_e6 / (1e6 * 10_000)
+        let denom = net_abs.saturating_mul(price_e6 as u128);
+        let num = buffer.saturating_mul(10_000).saturating_mul(1_000_000);
+        let bps = num / denom + if num % denom != 0 { 1 } else { 0 };
+        Some(core::cmp::min(bps, u32::MAX as u128) as u32)
+    }
+
+    // =========================================================================
+    // Trial balance residual (pure logic)
+    // =========================================================================
+
+    /// residual = vault - c_tot - insurance, signed, saturating at i128 bounds.
+    /// Exact whenever all three fit in i128 (always true for token-backed balances).
+    #[inline]
+    pub fn trial_balance_residual(vault: u128, c_tot: u128, insurance: u128) -> i128 {
+        let v = core::cmp::min(vault, i128::MAX as u128) as i128;
+        let c = core::cmp::min(c_tot, i128::MAX as u128) as i128;
+        let ins = core::cmp::min(insurance, i128::MAX as u128) as i128;
+        v.saturating_sub(c).saturating_sub(ins)
+    }
+
+    // =========================================================================
+    // Mark-to-market margin math (mirrors engine formulas)
+    // =========================================================================
+
+    /// Position notional: |pos| * price_e6 / 1e6 (saturating).
+    #[inline]
+    pub fn position_notional(position_size: i128, price_e6: u64) -> u128 {
+        position_size
+            .unsigned_abs()
+            .saturating_mul(price_e6 as u128)
+            / 1_000_000
+    }
+
+    /// Mark PnL: (price - entry) * |pos| / 1e6 for longs, (entry - price) for shorts.
+    /// Returns None on overflow (engine uses checked math here).
+    #[inline]
+    pub fn mark_pnl(position_size: i128, entry_price: u64, price_e6: u64) -> Option<i128> {
+        if position_size == 0 {
+            return Some(0);
+        }
+        let abs_pos = position_size.unsigned_abs();
+        if abs_pos > i128::MAX as u128 {
+            return None;
+        }
+        let diff: i128 = if position_size > 0 {
+            (price_e6 as i128) - (entry_price as i128)
+        } else {
+            (entry_price as i128) - (price_e6 as i128)
+        };
+        diff.checked_mul(abs_pos as i128)?.checked_div(1_000_000)
+    }
+
+    /// MTM equity: max(0, capital + pnl + mark_pnl) (saturating).
+    #[inline]
+    pub fn equity_mtm(capital: u128, pnl: i128, mark_pnl: i128) -> u128 {
+        let cap_i = core::cmp::min(capital, i128::MAX as u128) as i128;
+        let eq = cap_i.saturating_add(pnl).saturating_add(mark_pnl);
+        if eq > 0 {
+            eq as u128
+        } else {
+            0
+        }
+    }
+
+    /// Margin required: notional * bps / 10_000.
+    #[inline]
+    pub fn margin_required(notional: u128, margin_bps: u64) -> u128 {
+        notional.saturating_mul(margin_bps as u128) / 10_000
+    }
+
+    /// True iff an open position's MTM equity is at or below `margin_bps` of
+    /// notional (i.e. not strictly above, as the engine requires). Flat
+    /// positions are never below margin. Mark overflow counts as below.
+    #[inline]
+    pub fn below_margin_mtm(
+        capital: u128,
+        pnl: i128,
+        position_size: i128,
+        entry_price: u64,
+        price_e6: u64,
+        margin_bps: u64,
+    ) -> bool {
+        if position_size == 0 {
+            return false;
+        }
+        let mark = match mark_pnl(position_size, entry_price, price_e6) {
+            Some(m) => m,
+            None => return true,
+        };
+        let equity = equity_mtm(capital, pnl, mark);
+        let required = margin_required(position_notional(position_size, price_e6), margin_bps);
+        equity <= required
+    }
+
+    /// Account is safe under `old_bps` maintenance but would be liquidatable
+    /// under `new_bps`. Never true when the requirement is loosened.
+    #[inline]
+    pub fn newly_liquidatable(
+        capital: u128,
+        pnl: i128,
+        position_size: i128,
+        entry_price: u64,
+        price_e6: u64,
+        old_bps: u64,
+        new_bps: u64,
+    ) -> bool {
+        below_margin_mtm(capital, pnl, position_size, entry_price, price_e6, new_bps)
+            && !below_margin_mtm(capital, pnl, position_size, entry_price, price_e6, old_bps)
+    }
+
+    /// Zombie: nonzero position with zero MTM equity. Mark overflow is not
+    /// treated as a zombie; such accounts are left to the engine's own
+    /// maintenance check.
+    #[inline]
+    pub fn is_zombie(
+        capital: u128,
+        pnl: i128,
+        position_size: i128,
+        entry_price: u64,
+        price_e6: u64,
+    ) -> bool {
+        if position_size == 0 {
+            return false;
+        }
+        match mark_pnl(position_size, entry_price, price_e6) {
+            Some(mark) => equity_mtm(capital, pnl, mark) == 0,
+            None => false,
+        }
+    }
+
+    /// Margin health snapshot for one account at a given price.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct MarginHealth {
+        pub equity: u128,
+        pub maintenance_required: u128,
+        pub initial_required: u128,
+        /// below_margin_mtm at maintenance (equity not strictly above)
+        pub liquidatable: bool,
+    }
+
+    /// Build MarginHealth from account fields. Mark overflow reports zero
+    /// equity, consistent with below_margin_mtm.
+    #[inline]
+    pub fn margin_health(
+        capital: u128,
+        pnl: i128,
+        position_size: i128,
+        entry_price: u64,
+        price_e6: u64,
+        initial_margin_bps: u64,
+        maintenance_margin_bps: u64,
+    ) -> MarginHealth {
+        let equity = match mark_pnl(position_size, entry_price, price_e6) {
+            Some(mark) => equity_mtm(capital, pnl, mark),
+            None => 0,
+        };
+        let notional = position_notional(position_size, price_e6);
+        MarginHealth {
+            equity,
+            maintenance_required: margin_required(notional, maintenance_margin_bps),
+            initial_required: margin_required(notional, initial_margin_bps),
+            liquidatable: below_margin_mtm(
+                capital,
+                pnl,
+                position_size,
+                entry_price,
+                price_e6,
+                maintenance_margin_bps,
+            ),
+        }
+    }
+
+    /// Bankruptcy deficit at `price_e6`: max(0, -(capital + pnl + mark_pnl)).
+    /// Mark overflow reports 0 (treated as unknowable, not as a loss).
+    #[inline]
+    pub fn account_deficit(
+        capital: u128,
+        pnl: i128,
+        position_size: i128,
+        entry_price: u64,
+        price_e6: u64,
+    ) -> u128 {
+        let mark = match mark_pnl(position_size, entry_price, price_e6) {
+            Some(m) => m,
+            None => return 0,
+        };
+        let cap_i = core::cmp::min(capital, i128::MAX as u128) as i128;
+        let eq = cap_i.saturating_add(pnl).saturating_add(mark);
+        if eq < 0 {
+            eq.unsigned_abs()
+        } else {
+            0
+        }
+    }
+
+    /// Close at the mark: losses come out of capital first, gains become
+    /// profit. Returns (capital_after, profit). Bad debt beyond capital is
+    /// dropped (it is not payable to anyone).
+    #[inline]
+    pub fn close_at_mark(capital: u128, pnl: i128, mark_pnl: i128) -> (u128, u128) {
+        let net = pnl.saturating_add(mark_pnl);
+        if net >= 0 {
+            (capital, net as u128)
+        } else {
+            (capital.saturating_sub(net.unsigned_abs()), 0)
+        }
+    }
+
+    /// Wind-down payout for one account: capital is paid first (pro-rata only
+    /// if the vault cannot cover all capital), then profit is paid from the
+    /// residual vault - c_tot - insurance at the haircut min(residual, P) / P.
+    #[inline]
+    pub fn wind_down_payout(
+        capital: u128,
+        profit: u128,
+        c_tot: u128,
+        profit_tot: u128,
+        vault: u128,
+        insurance: u128,
+    ) -> u128 {
+        let cap_paid = if c_tot == 0 || vault >= c_tot {
+            capital
+        } else {
+            capital.saturating_mul(vault) / c_tot
+        };
+        let residual = vault.saturating_sub(c_tot).saturating_sub(insurance);
+        let profit_paid = if profit_tot == 0 {
+            0
+        } else {
+            profit.saturating_mul(core::cmp::min(residual, profit_tot)) / profit_tot
+        };
+        cap_paid.saturating_add(profit_paid)
+    }
+
+    /// Warmed PnL after `elapsed` slots: min(avail, slope * elapsed).
+    #[inline]
+    pub fn warmed_pnl(avail: u128, slope: u128, elapsed: u64) -> u128 {
+        core::cmp::min(avail, slope.saturating_mul(elapsed as u128))
+    }
+
+    /// Slots until warmed_pnl reaches `avail`: 0 if already warmed, u64::MAX
+    /// if the slope is zero with PnL outstanding, else
+    /// ceil(avail / slope) - elapsed (saturating).
+    #[inline]
+    pub fn slots_to_full_warmup(avail: u128, slope: u128, elapsed: u64) -> u64 {
+        if warmed_pnl(avail, slope, elapsed) >= avail {
+            return 0;
+        }
+        if slope == 0 {
+            return u64::MAX;
+        }
+        let needed = avail.div_ceil(slope);
+        core::cmp::min(needed - elapsed as u128, u64::MAX as u128) as u64
+    }
+
+    /// Liquidation price by binary search on below_margin_mtm over [1, max_price].
+    /// Long: largest p below margin with p + 1 above. Short: smallest p below
+    /// margin with p - 1 above. None if flat or no such boundary exists.
+    pub fn liquidation_price(
+        capital: u128,
+        pnl: i128,
+        position_size: i128,
+        entry_price: u64,
+        mm_bps: u64,
+        max_price: u64,
+    ) -> Option<u64> {
+        if position_size == 0 || max_price < 2 {
+            return None;
+        }
+        let below = |p: u64| below_margin_mtm(capital, pnl, position_size, entry_price, p, mm_bps);
+        // Invariant: `unsafe_p` is below margin, `safe_p` is not; they converge to neighbours.
+        let (mut unsafe_p, mut safe_p) = if position_size > 0 {
+            (1u64, max_price)
+        } else {
+            (max_price, 1u64)
+        };
+        if !below(unsafe_p) || below(safe_p) {
+            return None;
+        }
+        while unsafe_p.abs_diff(safe_p) > 1 {
+            let mid = core::cmp::min(unsafe_p, safe_p) + unsafe_p.abs_diff(safe_p) / 2;
+            if below(mid) {
+                unsafe_p = mid;
+            } else {
+                safe_p = mid;
+            }
+        }
+        Some(unsafe_p)
+    }
+
+    /// Trading fee on |size| at `price_e6`: ceil(notional * fee_bps / 10_000).
+    #[inline]
+    pub fn trade_fee(abs_size: u128, price_e6: u64, fee_bps: u64) -> u128 {
+        let notional = abs_size.saturating_mul(price_e6 as u128) / 1_000_000;
+        notional
+            .saturating_mul(fee_bps as u128)
+            .saturating_add(9_999)
+            / 10_000
+    }
+
+    /// Opening |size| = `abs_size` from flat with `collateral` is affordable iff
+    /// the trade fee ceil(notional * fee_bps / 10_000) is paid and the remaining
+    /// equity is strictly above initial margin. Monotone in abs_size.
+    #[inline]
+    pub fn open_size_affordable(
+        collateral: u128,
+        abs_size: u128,
+        price_e6: u64,
+        initial_margin_bps: u64,
+        fee_bps: u64,
+    ) -> bool {
+        let notional = abs_size.saturating_mul(price_e6 as u128) / 1_000_000;
+        let fee = trade_fee(abs_size, price_e6, fee_bps);
+        fee < collateral && collateral - fee > margin_required(notional, initial_margin_bps)
+    }
+
+    /// Per-slot carry: maintenance fee plus the funding the position pays at
+    /// `rate_bps_per_slot` (rate > 0 => longs pay). Negative means the
+    /// position earns carry. Flat positions pay only the fee.
+    #[inline]
+    pub fn carry_per_slot(
+        maintenance_fee_per_slot: u128,
+        position_size: i128,
+        price_e6: u64,
+        rate_bps_per_slot: i64,
+    ) -> i128 {
+        let notional = position_notional(position_size, price_e6).min(i128::MAX as u128) as i128;
+        let funding = notional.saturating_mul(rate_bps_per_slot as i128) / 10_000;
+        let funding = if position_size < 0 { -funding } else { funding };
+        (maintenance_fee_per_slot.min(i128::MAX as u128) as i128).saturating_add(funding)
+    }
+
+    /// Equity not already committed to the current position's initial
+    /// margin. 0 when the account is at or below initial margin.
+    #[inline]
+    pub fn free_equity(equity: u128, initial_required: u128) -> u128 {
+        equity.saturating_sub(initial_required)
+    }
+
+    /// Largest |size| in [0, max_abs] affordable with `collateral` (binary
+    /// search on open_size_affordable). 0 when even a zero-size open is not.
+    pub fn max_position_for_collateral(
+        collateral: u128,
+        price_e6: u64,
+        initial_margin_bps: u64,
+        fee_bps: u64,
+        max_abs: u128,
+    ) -> u128 {
+        let ok =
+            |s: u128| open_size_affordable(collateral, s, price_e6, initial_margin_bps, fee_bps);
+        if !ok(0) {
+            return 0;
+        }
+        if ok(max_abs) {
+            return max_abs;
+        }
+        // Invariant: ok(lo) && !ok(hi)
+        let (mut lo, mut hi) = (0u128, max_abs);
+        while hi - lo > 1 {
+            let mid = lo + (hi - lo) / 2;
+            if ok(mid) {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        lo
+    }
+
+    /// Entry is stale iff |oracle - entry| / entry > threshold_bps / 10_000,
+    /// i.e. |oracle - entry| * 10_000 > threshold_bps * entry. A zero entry
+    /// is stale whenever the oracle is nonzero.
+    #[inline]
+    pub fn entry_deviation_exceeds(entry_price: u64, price_e6: u64, threshold_bps: u16) -> bool {
+        let diff = entry_price.abs_diff(price_e6) as u128;
+        diff * 10_000 > threshold_bps as u128 * entry_price as u128
+    }
+
+    /// PnL claim if the account closed at the mark: max(0, pnl + mark_pnl).
+    #[inline]
+    pub fn pnl_claim_at_mark(pnl: i128, mark_pnl: i128) -> u128 {
+        let net = pnl.saturating_add(mark_pnl);
+        if net > 0 {
+            net as u128
+        } else {
+            0
+        }
+    }
+
+    /// System leverage guard: trades that do not grow OI always pass; otherwise
+    /// oi_after notional must be <= max_x * total_capital. max_x == 0 disables.
+    #[inline]
+    pub fn system_leverage_ok(
+        oi_before: u128,
+        oi_after: u128,
+        price_e6: u64,
+        total_capital: u128,
+        max_x: u64,
+    ) -> bool {
+        if max_x == 0 || oi_after <= oi_before {
+            return true;
+        }
+        let notional = oi_after.saturating_mul(price_e6 as u128) / 1_000_000;
+        notional <= total_capital.saturating_mul(max_x as u128)
+    }
+
+    /// Dead-man's switch blocks a trade iff enabled, it grew OI, and more than
+    /// `deadman_slots` have elapsed since the last crank. Reductions always pass.
+    #[inline]
+    pub fn crank_deadman_blocks(
+        now_slot: u64,
+        last_crank_slot: u64,
+        deadman_slots: u64,
+        oi_before: u128,
+        oi_after: u128,
+    ) -> bool {
+        deadman_slots != 0
+            && oi_after > oi_before
+            && now_slot.saturating_sub(last_crank_slot) > deadman_slots
+    }
+
+    /// Minimum-deposit floor blocks a deposit iff enabled, the target is a
+    /// user (LP deposits are exempt from user-only floors), amount <
+    /// min_deposit, and the deposit does not rescue the account (below
+    /// maintenance before, above after).
+    #[inline]
+    pub fn min_deposit_blocks(
+        amount: u64,
+        min_deposit: u64,
+        is_lp: bool,
+        below_before: bool,
+        below_after: bool,
+    ) -> bool {
+        let rescues = below_before && !below_after;
+        min_deposit != 0 && !is_lp && amount < min_deposit && !rescues
+    }
+
+    /// A new account is refused iff the cap is enabled and the owner already
+    /// holds `cap` or more used accounts.
+    #[inline]
+    pub fn owner_account_limit_reached(owned: u64, cap: u64) -> bool {
+        cap != 0 && owned >= cap
+    }
+
+    /// Liquidation close price is no worse than the caller's limit. Longs
+    /// close by selling (price must be >= limit), shorts by buying (price
+    /// must be <= limit). No limit or a flat position always passes.
+    #[inline]
+    pub fn liquidation_limit_ok(position_size: i128, price_e6: u64, limit_e6: Option<u64>) -> bool {
+        match limit_e6 {
+            None => true,
+            Some(limit) => {
+                if position_size > 0 {
+                    price_e6 >= limit
+                } else if position_size < 0 {
+                    price_e6 <= limit
+                } else {
+                    true
+                }
+            }
+        }
+    }
+
+    /// Slots until a move of `volatility_bps` per slot can exhaust
+    /// `buffer_bps`, capped at `max_staleness`. 0 volatility returns the cap.
+    #[inline]
+    pub fn crank_interval(volatility_bps: u16, buffer_bps: u16, max_staleness: u64) -> u64 {
+        if volatility_bps == 0 {
+            return max_staleness;
+        }
+        core::cmp::min((buffer_bps / volatility_bps) as u64, max_staleness)
+    }
+
+    /// Largest single |position| as a share of total OI in bps, capped at
+    /// 10_000. 0 when OI is zero.
+    #[inline]
+    pub fn concentration_bps(max_abs_pos: u128, total_oi: u128) -> u16 {
+        if total_oi == 0 {
+            return 0;
+        }
+        core::cmp::min(max_abs_pos.saturating_mul(10_000) / total_oi, 10_000) as u16
+    }
+
+    /// Trade size is representable on both sides: size != i128::MIN, so the
+    /// LP delta (-size) and |size| never overflow.
+    #[inline]
+    pub fn trade_size_ok(size: i128) -> bool {
+        size != i128::MIN
+    }
+
+    /// Margin params are valid iff 0 < maintenance <= initial <= 100%.
+    /// A zero maintenance margin would disable liquidation entirely.
+    #[inline]
+    pub fn margin_params_ok(initial_bps: u64, maintenance_bps: u64) -> bool {
+        maintenance_bps > 0 && initial_bps >= maintenance_bps && initial_bps <= 10_000
+    }
+
+    /// Withdrawal of `amount` is honorable iff it fits in the account's
+    /// capital after negative PnL is settled against it, the vault still
+    /// covers the other accounts' capital (c_tot - capital) plus insurance
+    /// once `amount` has left, and insurance is above the risk-reduction
+    /// threshold.
+    #[inline]
+    pub fn withdrawal_honorable(
+        vault: u128,
+        c_tot: u128,
+        insurance: u128,
+        capital: u128,
+        pnl: i128,
+        amount: u128,
+        threshold: u128,
+    ) -> bool {
+        let settled = capital.saturating_sub(pnl.min(0).unsigned_abs());
+        if amount > settled || amount > vault || capital > c_tot {
+            return false;
+        }
+        let others = match (c_tot - capital).checked_add(insurance) {
+            Some(o) => o,
+            None => return false,
+        };
+        vault - amount >= others && !gate_active(threshold, insurance)
+    }
+
+    /// Slots of projected losses insurance can absorb:
+    /// spendable / loss_per_slot, saturating; u64::MAX when loss_per_slot == 0.
+    #[inline]
+    pub fn insurance_runway_slots(spendable: u128, loss_per_slot: u128) -> u64 {
+        if loss_per_slot == 0 {
+            return u64::MAX;
+        }
+        core::cmp::min(spendable / loss_per_slot, u64::MAX as u128) as u64
+    }
+
+    /// Funding rate actually applied by a crank: 0 while funding is paused,
+    /// so the index holds while the engine still advances last_funding_slot.
+    #[inline]
+    pub fn effective_funding_rate(rate_bps_per_slot: i64, paused: bool) -> i64 {
+        if paused {
+            0
+        } else {
+            rate_bps_per_slot
+        }
+    }
+
+    /// Orphaned vault: max(0, vault - c_tot - insurance - pnl_pos_tot).
+    /// pnl_pos_tot is the most positive PnL the residual can be asked to back.
+    #[inline]
+    pub fn orphaned_vault(vault: u128, c_tot: u128, insurance: u128, pnl_pos_tot: u128) -> u128 {
+        vault
+            .saturating_sub(c_tot)
+            .saturating_sub(insurance)
+            .saturating_sub(pnl_pos_tot)
+    }
+}
+
+// 2. mod zc (Zero-Copy unsafe island)
+#[allow(unsafe_code)]
+pub mod zc {
+    use crate::constants::{ENGINE_ALIGN, ENGINE_LEN, ENGINE_OFF};
+    use core::mem::offset_of;
+    use percolator::RiskEngine;
+    use solana_program::program_error::ProgramError;
+
+    // Use const to export the actual offset for debugging
+    pub const ACCOUNTS_OFFSET: usize = offset_of!(RiskEngine, accounts);
+
+    /// Old slab length (before Account struct reordering migration)
+    /// Old slabs support up to 4095 accounts, new slabs support 4096.
+    const OLD_ENGINE_LEN: usize = ENGINE_LEN - 8;
+
+    #[inline]
+    pub fn engine_ref<'a>(data: &'a [u8]) -> Result<&'a RiskEngine, ProgramError> {
+        // Accept old slabs (ENGINE_LEN - 8) for backward compatibility
+        if data.len() < ENGINE_OFF + OLD_ENGINE_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let ptr = unsafe { data.as_ptr().add(ENGINE_OFF) };
+        if (ptr as usize) % ENGINE_ALIGN != 0 {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(unsafe { &*(ptr as *const RiskEngine) })
+    }
+
+    #[inline]
+    pub fn engine_mut<'a>(data: &'a mut [u8]) -> Result<&'a mut RiskEngine, ProgramError> {
+        // Accept old slabs (ENGINE_LEN - 8) for backward compatibility
+        if data.len() < ENGINE_OFF + OLD_ENGINE_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let ptr = unsafe { data.as_mut_ptr().add(ENGINE_OFF) };
+        if (ptr as usize) % ENGINE_ALIGN != 0 {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(unsafe { &mut *(ptr as *mut RiskEngine) })
+    }
+
+    // NOTE: engine_write was removed because it requires passing RiskEngine by value,
+    // which stack-allocates the ~6MB struct and causes stack overflow in BPF.
+    // Use engine_mut() + init_in_place() instead for initialization.
+
+    use solana_program::{
+        account_info::AccountInfo, instruction::Instruction as SolInstruction,
+        program::invoke_signed,
+    };
 
     /// Invoke the matcher program via CPI with proper lifetime coercion.
     ///
@@ -1040,6 +2581,13 @@ pub mod error {
         InvalidTokenProgram,
         InvalidConfigParam,
         HyperpTradeNoCpiDisabled,
+        EngineCorruptState,
+        InsuranceRatioTooLow,
+        WithdrawalNotHonorable,
+        SystemLeverageExceeded,
+        CrankStale,
+        DepositTooSmall,
+        OwnerAccountLimit,
     }
 
     impl From<PercolatorError> for ProgramError {
@@ -1116,6 +2664,8 @@ pub mod ix {
         },
         LiquidateAtOracle {
             target_idx: u16,
+            /// Worst acceptable close price (tag 32 only); None for tag 7.
+            limit_price_e6: Option<u64>,
         },
         CloseAccount {
             user_idx: u16,
@@ -1184,6 +2734,58 @@ pub mod ix {
         AdminForceCloseAccount {
             user_idx: u16,
         },
+        /// Set minimum insurance / open interest ratio for capital withdrawals (admin only).
+        /// min_insurance_to_oi_bps in basis points. 0 = disabled.
+        SetMinInsuranceRatio {
+            min_insurance_to_oi_bps: u64,
+        },
+        /// Credit orphaned vault surplus (not owed to capital, insurance or
+        /// positive PnL) to the insurance fund (admin only).
+        SweepOrphanedVault,
+        /// Update initial/maintenance margin bps on a live book (admin only).
+        /// Requires initial >= maintenance > 0. Does not liquidate; keepers find
+        /// newly unsafe accounts via accounts_below_maintenance.
+        SetMarginParams {
+            initial_margin_bps: u64,
+            maintenance_margin_bps: u64,
+        },
+        /// Toggle the strict withdrawal gate (admin only). When enabled,
+        /// WithdrawCollateral also requires system_can_honor_withdrawal.
+        SetStrictWithdraw {
+            enabled: u8,
+        },
+        /// Pause or resume funding accrual (admin only). While paused, cranks
+        /// accrue at a zero rate; resuming restarts accrual from the current slot.
+        /// Pausing requires a crank in the same slot (else CrankStale), so
+        /// funding up to the pause is not forfeited.
+        SetFundingPaused {
+            paused: u8,
+        },
+        /// Move capital from the signer's account into the insurance fund.
+        /// Vault is unchanged; the account is settled and must stay above
+        /// initial margin, as for WithdrawCollateral.
+        ContributeInsurance {
+            user_idx: u16,
+            amount: u64,
+        },
+        /// Set the system-wide leverage cap (admin only). 0 = disabled.
+        SetMaxSystemLeverage {
+            max_leverage_x: u64,
+        },
+        /// Set the crank dead-man's switch in slots (admin only). 0 = disabled.
+        SetCrankDeadman {
+            deadman_slots: u64,
+        },
+        /// Set the minimum deposit in base tokens (admin only). 0 = disabled.
+        SetMinDeposit {
+            min_deposit: u64,
+        },
+        /// Set the max accounts a single owner may hold (admin only). 0 = disabled.
+        SetMaxAccountsPerOwner {
+            max_accounts: u64,
+        },
+        /// Upgrade a version-1 slab to the current layout (permissionless).
+        MigrateSlab,
     }
 
     impl Instruction {
@@ -1267,7 +2869,10 @@ pub mod ix {
                 7 => {
                     // LiquidateAtOracle
                     let target_idx = read_u16(&mut rest)?;
-                    Ok(Instruction::LiquidateAtOracle { target_idx })
+                    Ok(Instruction::LiquidateAtOracle {
+                        target_idx,
+                        limit_price_e6: None,
+                    })
                 }
                 8 => {
                     // CloseAccount
@@ -1365,6 +2970,72 @@ pub mod ix {
                     let user_idx = read_u16(&mut rest)?;
                     Ok(Instruction::AdminForceCloseAccount { user_idx })
                 }
+                22 => {
+                    // SetMinInsuranceRatio
+                    let min_insurance_to_oi_bps = read_u64(&mut rest)?;
+                    Ok(Instruction::SetMinInsuranceRatio {
+                        min_insurance_to_oi_bps,
+                    })
+                }
+                23 => Ok(Instruction::SweepOrphanedVault),
+                24 => {
+                    // SetMarginParams
+                    let initial_margin_bps = read_u64(&mut rest)?;
+                    let maintenance_margin_bps = read_u64(&mut rest)?;
+                    Ok(Instruction::SetMarginParams {
+                        initial_margin_bps,
+                        maintenance_margin_bps,
+                    })
+                }
+                25 => {
+                    // SetStrictWithdraw
+                    let enabled = read_u8(&mut rest)?;
+                    Ok(Instruction::SetStrictWithdraw { enabled })
+                }
+                26 => {
+                    // SetFundingPaused
+                    let paused = read_u8(&mut rest)?;
+                    Ok(Instruction::SetFundingPaused { paused })
+                }
+                27 => {
+                    // ContributeInsurance
+                    let user_idx = read_u16(&mut rest)?;
+                    let amount = read_u64(&mut rest)?;
+                    Ok(Instruction::ContributeInsurance { user_idx, amount })
+                }
+                28 => {
+                    // SetMaxSystemLeverage
+                    let max_leverage_x = read_u64(&mut rest)?;
+                    Ok(Instruction::SetMaxSystemLeverage { max_leverage_x })
+                }
+                29 => {
+                    // SetCrankDeadman
+                    let deadman_slots = read_u64(&mut rest)?;
+                    Ok(Instruction::SetCrankDeadman { deadman_slots })
+                }
+                30 => {
+                    // SetMinDeposit
+                    let min_deposit = read_u64(&mut rest)?;
+                    Ok(Instruction::SetMinDeposit { min_deposit })
+                }
+                31 => {
+                    // SetMaxAccountsPerOwner
+                    let max_accounts = read_u64(&mut rest)?;
+                    Ok(Instruction::SetMaxAccountsPerOwner { max_accounts })
+                }
+                32 => {
+                    // LiquidateAtOracleLimited
+                    let target_idx = read_u16(&mut rest)?;
+                    let limit_price_e6 = read_u64(&mut rest)?;
+                    Ok(Instruction::LiquidateAtOracle {
+                        target_idx,
+                        limit_price_e6: Some(limit_price_e6),
+                    })
+                }
+                33 => {
+                    // MigrateSlab
+                    Ok(Instruction::MigrateSlab)
+                }
                 _ => Err(ProgramError::InvalidInstructionData),
             }
         }
@@ -1520,7 +3191,9 @@ pub mod accounts {
 
 // 6. mod state
 pub mod state {
-    use crate::constants::{CONFIG_LEN, HEADER_LEN};
+    use crate::constants::{
+        CONFIG_LEN, ENGINE_OFF, HEADER_LEN, V1_CONFIG_LEN, V1_ENGINE_OFF, VERSION,
+    };
     use bytemuck::{Pod, Zeroable};
     use core::cell::RefMut;
     use core::mem::offset_of;
@@ -1615,6 +3288,26 @@ pub mod state {
         /// Last effective oracle price (after clamping), in e6 format.
         /// 0 = no history (first price accepted as-is).
         pub last_effective_price_e6: u64,
+
+        // ========================================
+        // Wrapper Policy Parameters
+        // ========================================
+        /// Minimum insurance / total_open_interest ratio in basis points.
+        /// Capital withdrawals are rejected while the ratio is below it. 0 = disabled.
+        pub min_insurance_to_oi_bps: u64,
+        /// Max system leverage: total OI notional / total capital, as a multiple.
+        /// Trades that grow OI beyond it are rejected. 0 = disabled.
+        pub max_system_leverage_x: u64,
+        /// Dead-man's switch: OI-increasing trades are rejected once more than
+        /// this many slots have passed since the last crank. 0 = disabled.
+        pub crank_deadman_slots: u64,
+        /// Minimum deposit in base tokens. Smaller deposits are rejected unless
+        /// they lift an account from below to above maintenance. 0 = disabled.
+        pub min_deposit: u64,
+        /// Max used accounts (users + LPs) a single owner may hold. 0 = disabled.
+        pub max_accounts_per_owner: u64,
+        /// Reserved for further policy knobs (keeps layout stable when added).
+        pub _policy_reserved: [u64; 1],
     }
 
     pub fn slab_data_mut<'a, 'b>(
@@ -1690,6 +3383,12 @@ pub mod state {
     /// Flag bit: Market is resolved (withdraw-only mode)
     pub const FLAG_RESOLVED: u8 = 1 << 0;
 
+    /// Flag bit: Withdrawals must also pass the system-level backing check
+    pub const FLAG_STRICT_WITHDRAW: u8 = 1 << 1;
+
+    /// Flag bit: Funding accrual is paused (cranks apply a zero rate)
+    pub const FLAG_FUNDING_PAUSED: u8 = 1 << 2;
+
     /// Read market flags from _padding[0].
     pub fn read_flags(data: &[u8]) -> u8 {
         data[FLAGS_OFF]
@@ -1711,6 +3410,36 @@ pub mod state {
         write_flags(data, flags);
     }
 
+    /// Check if the strict (system-backing) withdrawal gate is enabled.
+    pub fn is_strict_withdraw(data: &[u8]) -> bool {
+        read_flags(data) & FLAG_STRICT_WITHDRAW != 0
+    }
+
+    /// Enable or disable the strict withdrawal gate.
+    pub fn set_strict_withdraw(data: &mut [u8], enabled: bool) {
+        let flags = if enabled {
+            read_flags(data) | FLAG_STRICT_WITHDRAW
+        } else {
+            read_flags(data) & !FLAG_STRICT_WITHDRAW
+        };
+        write_flags(data, flags);
+    }
+
+    /// Check if funding accrual is paused.
+    pub fn is_funding_paused(data: &[u8]) -> bool {
+        read_flags(data) & FLAG_FUNDING_PAUSED != 0
+    }
+
+    /// Pause or resume funding accrual.
+    pub fn set_funding_paused(data: &mut [u8], paused: bool) {
+        let flags = if paused {
+            read_flags(data) | FLAG_FUNDING_PAUSED
+        } else {
+            read_flags(data) & !FLAG_FUNDING_PAUSED
+        };
+        write_flags(data, flags);
+    }
+
     pub fn read_config(data: &[u8]) -> MarketConfig {
         let mut c = MarketConfig::zeroed();
         let src = &data[HEADER_LEN..HEADER_LEN + CONFIG_LEN];
@@ -1724,6 +3453,19 @@ pub mod state {
         let dst = &mut data[HEADER_LEN..HEADER_LEN + CONFIG_LEN];
         dst.copy_from_slice(src);
     }
+
+    /// Rewrite a version-1 slab of `v1_len` bytes into the current layout.
+    /// `data` must already be grown by ENGINE_OFF - V1_ENGINE_OFF. Shifts the
+    /// engine up, zeroes the new policy block (every knob disabled) and bumps
+    /// the header version. Header and v1 config bytes are kept as-is.
+    pub fn migrate_v1_layout(data: &mut [u8], v1_len: usize) {
+        debug_assert_eq!(data.len(), v1_len + ENGINE_OFF - V1_ENGINE_OFF);
+        data.copy_within(V1_ENGINE_OFF..v1_len, ENGINE_OFF);
+        data[HEADER_LEN + V1_CONFIG_LEN..ENGINE_OFF].fill(0);
+        let mut h = read_header(data);
+        h.version = VERSION;
+        write_header(data, &h);
+    }
 }
 
 // 7. mod units - base token/units conversion at instruction boundaries
@@ -2383,8 +4125,9 @@ pub mod processor {
             DEFAULT_FUNDING_MAX_PREMIUM_BPS, DEFAULT_HYPERP_PRICE_CAP_E2BPS,
             DEFAULT_THRESH_ALPHA_BPS, DEFAULT_THRESH_FLOOR, DEFAULT_THRESH_MAX, DEFAULT_THRESH_MIN,
             DEFAULT_THRESH_MIN_STEP, DEFAULT_THRESH_RISK_BPS, DEFAULT_THRESH_STEP_BPS,
-            DEFAULT_THRESH_UPDATE_INTERVAL_SLOTS, MAGIC, MATCHER_CALL_LEN, MATCHER_CALL_TAG,
-            MATCHER_CONTEXT_LEN, MATCHER_CONTEXT_PREFIX_LEN, SLAB_LEN, VERSION,
+            DEFAULT_THRESH_UPDATE_INTERVAL_SLOTS, ENGINE_OFF, MAGIC, MATCHER_CALL_LEN,
+            MATCHER_CALL_TAG, MATCHER_CONTEXT_LEN, MATCHER_CONTEXT_PREFIX_LEN, SLAB_LEN,
+            V1_ENGINE_OFF, V1_SLAB_LEN, V1_VERSION, VERSION,
         },
         error::{map_risk_error, PercolatorError},
         ix::Instruction,
@@ -2394,6 +4137,7 @@ pub mod processor {
     };
     use percolator::{
         MatchingEngine, NoOpMatcher, RiskEngine, RiskError, TradeExecution, MAX_ACCOUNTS,
+        MAX_ORACLE_PRICE,
     };
     use solana_program::instruction::{AccountMeta, Instruction as SolInstruction};
     use solana_program::{
@@ -2480,6 +4224,75 @@ pub mod processor {
         Ok(())
     }
 
+    /// Post-trade sanity: a nonzero position must carry a valid entry price.
+    /// A zero entry on an open position would corrupt every later MTM calc.
+    fn check_entry_price(engine: &RiskEngine, idx: u16) -> Result<(), ProgramError> {
+        let acc = &engine.accounts[idx as usize];
+        if !crate::verify::entry_price_ok(
+            acc.position_size.get(),
+            acc.entry_price,
+            MAX_ORACLE_PRICE,
+        ) {
+            return Err(PercolatorError::EngineCorruptState.into());
+        }
+        Ok(())
+    }
+
+    /// Post-trade system leverage check against the OI seen before the trade.
+    /// Total capital (c_tot) stands in for total equity, ignoring unrealized PnL.
+    fn check_system_leverage(
+        engine: &RiskEngine,
+        oi_before: u128,
+        price: u64,
+        max_x: u64,
+    ) -> Result<(), ProgramError> {
+        if !crate::verify::system_leverage_ok(
+            oi_before,
+            engine.total_open_interest.get(),
+            price,
+            engine.c_tot.get(),
+            max_x,
+        ) {
+            return Err(PercolatorError::SystemLeverageExceeded.into());
+        }
+        Ok(())
+    }
+
+    /// Post-trade dead-man's switch: reject OI growth while the crank is stale.
+    fn check_crank_deadman(
+        engine: &RiskEngine,
+        oi_before: u128,
+        now_slot: u64,
+        deadman_slots: u64,
+    ) -> Result<(), ProgramError> {
+        if crate::verify::crank_deadman_blocks(
+            now_slot,
+            engine.last_crank_slot,
+            deadman_slots,
+            oi_before,
+            engine.total_open_interest.get(),
+        ) {
+            return Err(PercolatorError::CrankStale.into());
+        }
+        Ok(())
+    }
+
+    /// Per-owner account cap, checked before add_user/add_lp.
+    fn check_owner_account_limit(
+        engine: &RiskEngine,
+        owner: &Pubkey,
+        cap: u64,
+    ) -> Result<(), ProgramError> {
+        if cap == 0 {
+            return Ok(());
+        }
+        let owned = crate::owner_account_count(engine, &owner.to_bytes());
+        if crate::verify::owner_account_limit_reached(owned, cap) {
+            return Err(PercolatorError::OwnerAccountLimit.into());
+        }
+        Ok(())
+    }
+
     fn verify_vault(
         a_vault: &AccountInfo,
         expected_owner: &Pubkey,
@@ -2714,6 +4527,13 @@ pub mod processor {
                         0
                     },
                     last_effective_price_e6: if is_hyperp { initial_mark_price_e6 } else { 0 },
+                    // Wrapper policy: all disabled by default
+                    min_insurance_to_oi_bps: 0,
+                    max_system_leverage_x: 0,
+                    crank_deadman_slots: 0,
+                    min_deposit: 0,
+                    max_accounts_per_owner: 0,
+                    _policy_reserved: [0; 1],
                 };
                 state::write_config(&mut data, &config);
 
@@ -2774,6 +4594,7 @@ pub mod processor {
                 state::write_dust_base(&mut data, old_dust.saturating_add(dust));
 
                 let engine = zc::engine_mut(&mut data)?;
+                check_owner_account_limit(engine, a_user.key, config.max_accounts_per_owner)?;
                 let idx = engine.add_user(units as u128).map_err(map_risk_error)?;
                 engine
                     .set_owner(idx, a_user.key.to_bytes())
@@ -2827,6 +4648,7 @@ pub mod processor {
                 state::write_dust_base(&mut data, old_dust.saturating_add(dust));
 
                 let engine = zc::engine_mut(&mut data)?;
+                check_owner_account_limit(engine, a_user.key, config.max_accounts_per_owner)?;
                 let idx = engine
                     .add_lp(
                         matcher_program.to_bytes(),
@@ -2894,6 +4716,34 @@ pub mod processor {
                     return Err(PercolatorError::EngineUnauthorized.into());
                 }
 
+                // Minimum deposit floor; rescues are judged at the last effective
+                // oracle price (deposits carry no oracle account)
+                if config.min_deposit != 0 {
+                    let acc = &engine.accounts[user_idx as usize];
+                    let price = config.last_effective_price_e6;
+                    let below = |capital: u128| {
+                        price != 0
+                            && crate::verify::below_margin_mtm(
+                                capital,
+                                acc.pnl.get(),
+                                acc.position_size.get(),
+                                acc.entry_price,
+                                price,
+                                engine.params.maintenance_margin_bps,
+                            )
+                    };
+                    let capital = acc.capital.get();
+                    if crate::verify::min_deposit_blocks(
+                        amount,
+                        config.min_deposit,
+                        acc.is_lp(),
+                        below(capital),
+                        below(capital.saturating_add(units as u128)),
+                    ) {
+                        return Err(PercolatorError::DepositTooSmall.into());
+                    }
+                }
+
                 engine
                     .deposit(user_idx, units as u128, clock.slot)
                     .map_err(map_risk_error)?;
@@ -2943,6 +4793,7 @@ pub mod processor {
                     oracle::read_price_clamped(&mut config, a_oracle_idx, clock.unix_timestamp)?
                 };
                 state::write_config(&mut data, &config);
+                let strict_withdraw = state::is_strict_withdraw(&data);
 
                 let engine = zc::engine_mut(&mut data)?;
 
@@ -2959,9 +4810,30 @@ pub mod processor {
                     return Err(ProgramError::InvalidInstructionData);
                 }
 
+                // Block capital withdrawals while insurance is thin relative to OI.
+                // CloseAccount is deliberately not gated (exits stay open).
+                if crate::verify::insurance_ratio_blocks_withdraw(
+                    engine.insurance_fund.balance.get(),
+                    engine.total_open_interest.get(),
+                    config.min_insurance_to_oi_bps,
+                ) {
+                    return Err(PercolatorError::InsuranceRatioTooLow.into());
+                }
+
                 // Convert requested base tokens to units
                 let (units_requested, _) = crate::units::base_to_units(amount, config.unit_scale);
 
+                // Optional system-level backing gate (opt-in via SetStrictWithdraw)
+                if strict_withdraw
+                    && !crate::system_can_honor_withdrawal(
+                        engine,
+                        user_idx,
+                        units_requested as u128,
+                    )
+                {
+                    return Err(PercolatorError::WithdrawalNotHonorable.into());
+                }
+
                 engine
                     .withdraw(user_idx, units_requested as u128, clock.slot, price)
                     .map_err(map_risk_error)?;
@@ -3090,6 +4962,7 @@ pub mod processor {
                 let header = state::read_header(&data);
                 // Read last threshold update slot BEFORE mutable engine borrow
                 let last_thr_slot = state::read_last_thr_update_slot(&data);
+                let funding_paused = state::is_funding_paused(&data);
 
                 // SECURITY (C4): allow_panic triggers global settlement - admin only
                 // This prevents griefing attacks where anyone triggers panic at worst moment
@@ -3150,8 +5023,10 @@ pub mod processor {
                         config.funding_max_bps_per_slot,
                     );
 
-                    // Store new rate in config for next crank
-                    config.authority_timestamp = new_rate;
+                    // Store new rate in config for next crank (zero while paused,
+                    // so resuming does not apply a rate computed during the pause)
+                    config.authority_timestamp =
+                        crate::verify::effective_funding_rate(new_rate, funding_paused);
 
                     Some(prev_rate) // Use PREVIOUS rate for this crank (piecewise-constant model)
                 } else {
@@ -3199,6 +5074,10 @@ pub mod processor {
                         config.funding_max_bps_per_slot,
                     )
                 };
+                // Funding pause: accrue at zero so the index holds
+                let effective_funding_rate =
+                    crate::verify::effective_funding_rate(effective_funding_rate, funding_paused);
+
                 #[cfg(feature = "cu-audit")]
                 {
                     msg!("CU_CHECKPOINT: keeper_crank_start");
@@ -3219,6 +5098,26 @@ pub mod processor {
                     sol_log_compute_units();
                 }
 
+                // Zombies (open position, zero equity) in the window just ahead
+                // of the sweep cursor are closed now instead of waiting for the
+                // sweep to reach them. Runs after funding and fees are applied;
+                // the engine re-checks maintenance and may decline the close.
+                const ZOMBIE_SCAN_WINDOW: u16 = 256;
+                const ZOMBIE_BUDGET: usize = 8;
+                let mut zombies = [0u16; ZOMBIE_BUDGET];
+                let n_zombies = crate::zombie_accounts(
+                    engine,
+                    price,
+                    engine.crank_cursor,
+                    ZOMBIE_SCAN_WINDOW,
+                    &mut zombies,
+                );
+                for &idx in &zombies[..n_zombies] {
+                    engine
+                        .liquidate_at_oracle(idx, clock.slot, price)
+                        .map_err(map_risk_error)?;
+                }
+
                 // Dust sweep: if accumulated dust >= unit_scale, sweep to insurance fund
                 // Done before copying stats so insurance balance reflects the sweep
                 let remaining_dust = if unit_scale > 0 {
@@ -3299,6 +5198,11 @@ pub mod processor {
                 accounts::expect_signer(a_lp)?;
                 accounts::expect_writable(a_slab)?;
 
+                // Reject i128::MIN before any state change (negation would overflow)
+                if !crate::verify::trade_size_ok(size) {
+                    return Err(PercolatorError::EngineOverflow.into());
+                }
+
                 let mut data = state::slab_data_mut(a_slab)?;
                 slab_guard(program_id, a_slab, &data)?;
                 require_initialized(&data)?;
@@ -3369,9 +5273,14 @@ pub mod processor {
                     msg!("CU_CHECKPOINT: trade_nocpi_execute_start");
                     sol_log_compute_units();
                 }
+                let oi_before = engine.total_open_interest.get();
                 engine
                     .execute_trade(&NoOpMatcher, lp_idx, user_idx, clock.slot, price, size)
                     .map_err(map_risk_error)?;
+                check_entry_price(engine, user_idx)?;
+                check_entry_price(engine, lp_idx)?;
+                check_system_leverage(engine, oi_before, price, config.max_system_leverage_x)?;
+                check_crank_deadman(engine, oi_before, clock.slot, config.crank_deadman_slots)?;
                 #[cfg(feature = "cu-audit")]
                 {
                     msg!("CU_CHECKPOINT: trade_nocpi_execute_end");
@@ -3401,6 +5310,11 @@ pub mod processor {
                 accounts::expect_writable(a_slab)?;
                 accounts::expect_writable(a_matcher_ctx)?;
 
+                // Reject i128::MIN before any state change (negation would overflow)
+                if !crate::verify::trade_size_ok(size) {
+                    return Err(PercolatorError::EngineOverflow.into());
+                }
+
                 // Matcher shape validation via verify helper (Kani-provable)
                 let matcher_shape = crate::verify::MatcherAccountsShape {
                     prog_executable: a_matcher_prog.executable,
@@ -3599,9 +5513,14 @@ pub mod processor {
                         msg!("CU_CHECKPOINT: trade_cpi_execute_start");
                         sol_log_compute_units();
                     }
+                    let oi_before = engine.total_open_interest.get();
                     engine
                         .execute_trade(&matcher, lp_idx, user_idx, clock.slot, price, trade_size)
                         .map_err(map_risk_error)?;
+                    check_entry_price(engine, user_idx)?;
+                    check_entry_price(engine, lp_idx)?;
+                    check_system_leverage(engine, oi_before, price, config.max_system_leverage_x)?;
+                    check_crank_deadman(engine, oi_before, clock.slot, config.crank_deadman_slots)?;
                     #[cfg(feature = "cu-audit")]
                     {
                         msg!("CU_CHECKPOINT: trade_cpi_execute_end");
@@ -3626,7 +5545,10 @@ pub mod processor {
                     }
                 }
             }
-            Instruction::LiquidateAtOracle { target_idx } => {
+            Instruction::LiquidateAtOracle {
+                target_idx,
+                limit_price_e6,
+            } => {
                 accounts::expect_len(accounts, 4)?;
                 let a_slab = &accounts[1];
                 let a_oracle = &accounts[3];
@@ -3655,6 +5577,18 @@ pub mod processor {
 
                 check_idx(engine, target_idx)?;
 
+                // Limited variant: skip the close rather than liquidate into a
+                // bad print. The oracle read above is already cached in config;
+                // the engine is left untouched.
+                if !crate::verify::liquidation_limit_ok(
+                    engine.accounts[target_idx as usize].position_size.get(),
+                    price,
+                    limit_price_e6,
+                ) {
+                    msg!("Liquidation skipped: oracle price worse than limit");
+                    return Ok(());
+                }
+
                 // Debug logging for liquidation (using sol_log_64 for no_std)
                 sol_log_64(target_idx as u64, price, 0, 0, 0); // idx, price
                 {
@@ -4357,6 +6291,324 @@ pub mod processor {
                     &signer_seeds,
                 )?;
             }
+
+            Instruction::SetMinInsuranceRatio {
+                min_insurance_to_oi_bps,
+            } => {
+                accounts::expect_len(accounts, 2)?;
+                let a_admin = &accounts[0];
+                let a_slab = &accounts[1];
+
+                accounts::expect_signer(a_admin)?;
+                accounts::expect_writable(a_slab)?;
+
+                let mut data = state::slab_data_mut(a_slab)?;
+                slab_guard(program_id, a_slab, &data)?;
+                require_initialized(&data)?;
+                if state::is_resolved(&data) {
+                    return Err(ProgramError::InvalidAccountData);
+                }
+
+                let header = state::read_header(&data);
+                require_admin(header.admin, a_admin.key)?;
+
+                let mut config = state::read_config(&data);
+                config.min_insurance_to_oi_bps = min_insurance_to_oi_bps;
+                state::write_config(&mut data, &config);
+            }
+
+            Instruction::SweepOrphanedVault => {
+                accounts::expect_len(accounts, 2)?;
+                let a_admin = &accounts[0];
+                let a_slab = &accounts[1];
+
+                accounts::expect_signer(a_admin)?;
+                accounts::expect_writable(a_slab)?;
+
+                let mut data = state::slab_data_mut(a_slab)?;
+                slab_guard(program_id, a_slab, &data)?;
+                require_initialized(&data)?;
+                // Allowed after resolution: orphaned funds would otherwise be
+                // stranded (CloseSlab requires an empty vault).
+
+                let header = state::read_header(&data);
+                require_admin(header.admin, a_admin.key)?;
+
+                let engine = zc::engine_mut(&mut data)?;
+                let swept = crate::sweep_orphaned_to_insurance(engine);
+                sol_log_64(swept as u64, (swept >> 64) as u64, 0, 0, 0); // swept lo, hi
+            }
+
+            Instruction::SetMarginParams {
+                initial_margin_bps,
+                maintenance_margin_bps,
+            } => {
+                accounts::expect_len(accounts, 2)?;
+                let a_admin = &accounts[0];
+                let a_slab = &accounts[1];
+
+                accounts::expect_signer(a_admin)?;
+                accounts::expect_writable(a_slab)?;
+
+                let mut data = state::slab_data_mut(a_slab)?;
+                slab_guard(program_id, a_slab, &data)?;
+                require_initialized(&data)?;
+                if state::is_resolved(&data) {
+                    return Err(ProgramError::InvalidAccountData);
+                }
+
+                let header = state::read_header(&data);
+                require_admin(header.admin, a_admin.key)?;
+
+                if !crate::verify::margin_params_ok(initial_margin_bps, maintenance_margin_bps) {
+                    return Err(PercolatorError::InvalidConfigParam.into());
+                }
+
+                let engine = zc::engine_mut(&mut data)?;
+                engine.params.initial_margin_bps = initial_margin_bps;
+                engine.params.maintenance_margin_bps = maintenance_margin_bps;
+            }
+
+            Instruction::SetStrictWithdraw { enabled } => {
+                accounts::expect_len(accounts, 2)?;
+                let a_admin = &accounts[0];
+                let a_slab = &accounts[1];
+
+                accounts::expect_signer(a_admin)?;
+                accounts::expect_writable(a_slab)?;
+
+                let mut data = state::slab_data_mut(a_slab)?;
+                slab_guard(program_id, a_slab, &data)?;
+                require_initialized(&data)?;
+                if state::is_resolved(&data) {
+                    return Err(ProgramError::InvalidAccountData);
+                }
+
+                let header = state::read_header(&data);
+                require_admin(header.admin, a_admin.key)?;
+
+                state::set_strict_withdraw(&mut data, enabled != 0);
+            }
+
+            Instruction::SetFundingPaused { paused } => {
+                accounts::expect_len(accounts, 3)?;
+                let a_admin = &accounts[0];
+                let a_slab = &accounts[1];
+                let a_clock = &accounts[2];
+
+                accounts::expect_signer(a_admin)?;
+                accounts::expect_writable(a_slab)?;
+
+                let mut data = state::slab_data_mut(a_slab)?;
+                slab_guard(program_id, a_slab, &data)?;
+                require_initialized(&data)?;
+                if state::is_resolved(&data) {
+                    return Err(ProgramError::InvalidAccountData);
+                }
+
+                let header = state::read_header(&data);
+                require_admin(header.admin, a_admin.key)?;
+
+                let clock = Clock::from_account_info(a_clock)?;
+                let was_paused = state::is_funding_paused(&data);
+
+                // Pausing zeroes the rate for the whole window since the last
+                // crank, so funding must already be accrued up to this slot.
+                if !was_paused && paused != 0 {
+                    let engine = zc::engine_ref(&data)?;
+                    if engine.last_funding_slot < clock.slot {
+                        return Err(PercolatorError::CrankStale.into());
+                    }
+                }
+                state::set_funding_paused(&mut data, paused != 0);
+
+                // On resume, restart accrual from now so the slots since the last
+                // paused crank are not charged retroactively at the resumed rate.
+                if was_paused && paused == 0 {
+                    let engine = zc::engine_mut(&mut data)?;
+                    engine.last_funding_slot = engine.last_funding_slot.max(clock.slot);
+                }
+            }
+
+            Instruction::ContributeInsurance { user_idx, amount } => {
+                accounts::expect_len(accounts, 4)?;
+                let a_user = &accounts[0];
+                let a_slab = &accounts[1];
+                let a_clock = &accounts[2];
+                let a_oracle_idx = &accounts[3];
+
+                accounts::expect_signer(a_user)?;
+                accounts::expect_writable(a_slab)?;
+
+                let mut data = state::slab_data_mut(a_slab)?;
+                slab_guard(program_id, a_slab, &data)?;
+                require_initialized(&data)?;
+                if state::is_resolved(&data) {
+                    return Err(ProgramError::InvalidAccountData);
+                }
+
+                let mut config = state::read_config(&data);
+                let clock = Clock::from_account_info(a_clock)?;
+                let price = if oracle::is_hyperp_mode(&config) {
+                    let idx = config.last_effective_price_e6;
+                    if idx == 0 {
+                        return Err(PercolatorError::OracleInvalid.into());
+                    }
+                    idx
+                } else {
+                    oracle::read_price_clamped(&mut config, a_oracle_idx, clock.unix_timestamp)?
+                };
+                state::write_config(&mut data, &config);
+
+                let engine = zc::engine_mut(&mut data)?;
+
+                check_idx(engine, user_idx)?;
+
+                let owner = engine.accounts[user_idx as usize].owner;
+                if !crate::verify::owner_ok(owner, a_user.key.to_bytes()) {
+                    return Err(PercolatorError::EngineUnauthorized.into());
+                }
+
+                if config.unit_scale != 0 && amount % config.unit_scale as u64 != 0 {
+                    return Err(ProgramError::InvalidInstructionData);
+                }
+                let (units, _) = crate::units::base_to_units(amount, config.unit_scale);
+                let units = units as u128;
+
+                // Withdraw settles funding, fees and warmup, then enforces
+                // initial margin; topping up re-credits the same units to
+                // insurance, so the vault nets out unchanged.
+                engine
+                    .withdraw(user_idx, units, clock.slot, price)
+                    .map_err(map_risk_error)?;
+                engine
+                    .top_up_insurance_fund(units)
+                    .map_err(map_risk_error)?;
+            }
+
+            Instruction::SetMaxSystemLeverage { max_leverage_x } => {
+                accounts::expect_len(accounts, 2)?;
+                let a_admin = &accounts[0];
+                let a_slab = &accounts[1];
+
+                accounts::expect_signer(a_admin)?;
+                accounts::expect_writable(a_slab)?;
+
+                let mut data = state::slab_data_mut(a_slab)?;
+                slab_guard(program_id, a_slab, &data)?;
+                require_initialized(&data)?;
+                if state::is_resolved(&data) {
+                    return Err(ProgramError::InvalidAccountData);
+                }
+
+                let header = state::read_header(&data);
+                require_admin(header.admin, a_admin.key)?;
+
+                let mut config = state::read_config(&data);
+                config.max_system_leverage_x = max_leverage_x;
+                state::write_config(&mut data, &config);
+            }
+
+            Instruction::SetCrankDeadman { deadman_slots } => {
+                accounts::expect_len(accounts, 2)?;
+                let a_admin = &accounts[0];
+                let a_slab = &accounts[1];
+
+                accounts::expect_signer(a_admin)?;
+                accounts::expect_writable(a_slab)?;
+
+                let mut data = state::slab_data_mut(a_slab)?;
+                slab_guard(program_id, a_slab, &data)?;
+                require_initialized(&data)?;
+                if state::is_resolved(&data) {
+                    return Err(ProgramError::InvalidAccountData);
+                }
+
+                let header = state::read_header(&data);
+                require_admin(header.admin, a_admin.key)?;
+
+                let mut config = state::read_config(&data);
+                config.crank_deadman_slots = deadman_slots;
+                state::write_config(&mut data, &config);
+            }
+
+            Instruction::SetMinDeposit { min_deposit } => {
+                accounts::expect_len(accounts, 2)?;
+                let a_admin = &accounts[0];
+                let a_slab = &accounts[1];
+
+                accounts::expect_signer(a_admin)?;
+                accounts::expect_writable(a_slab)?;
+
+                let mut data = state::slab_data_mut(a_slab)?;
+                slab_guard(program_id, a_slab, &data)?;
+                require_initialized(&data)?;
+                if state::is_resolved(&data) {
+                    return Err(ProgramError::InvalidAccountData);
+                }
+
+                let header = state::read_header(&data);
+                require_admin(header.admin, a_admin.key)?;
+
+                let mut config = state::read_config(&data);
+                config.min_deposit = min_deposit;
+                state::write_config(&mut data, &config);
+            }
+
+            Instruction::SetMaxAccountsPerOwner { max_accounts } => {
+                accounts::expect_len(accounts, 2)?;
+                let a_admin = &accounts[0];
+                let a_slab = &accounts[1];
+
+                accounts::expect_signer(a_admin)?;
+                accounts::expect_writable(a_slab)?;
+
+                let mut data = state::slab_data_mut(a_slab)?;
+                slab_guard(program_id, a_slab, &data)?;
+                require_initialized(&data)?;
+                if state::is_resolved(&data) {
+                    return Err(ProgramError::InvalidAccountData);
+                }
+
+                let header = state::read_header(&data);
+                require_admin(header.admin, a_admin.key)?;
+
+                let mut config = state::read_config(&data);
+                config.max_accounts_per_owner = max_accounts;
+                state::write_config(&mut data, &config);
+            }
+
+            Instruction::MigrateSlab => {
+                accounts::expect_len(accounts, 1)?;
+                let a_slab = &accounts[0];
+                accounts::expect_writable(a_slab)?;
+                if a_slab.owner != program_id {
+                    return Err(ProgramError::IllegalOwner);
+                }
+
+                // Old slabs may also be 8 bytes short (see slab_guard).
+                let v1_len = a_slab.data_len();
+                if v1_len != V1_SLAB_LEN && v1_len != V1_SLAB_LEN - 8 {
+                    return Err(PercolatorError::InvalidSlabLen.into());
+                }
+                {
+                    let data = a_slab.try_borrow_data()?;
+                    let h = state::read_header(&data);
+                    if h.magic != MAGIC {
+                        return Err(PercolatorError::NotInitialized.into());
+                    }
+                    if h.version != V1_VERSION {
+                        return Err(PercolatorError::InvalidVersion.into());
+                    }
+                }
+
+                // The runtime rejects the grow unless the slab already holds
+                // rent-exempt lamports for the new length; callers top it up
+                // in the same transaction.
+                a_slab.realloc(v1_len + ENGINE_OFF - V1_ENGINE_OFF, false)?;
+                let mut data = state::slab_data_mut(a_slab)?;
+                state::migrate_v1_layout(&mut data, v1_len);
+            }
         }
         Ok(())
     }
@@ -29,10 +29,10 @@ use std::path::PathBuf;
 
 // SLAB_LEN for SBF - differs between test and production
 #[cfg(feature = "test")]
-const SLAB_LEN: usize = 16312; // MAX_ACCOUNTS=64 - haircut-ratio engine + oracle circuit breaker (no padding)
+const SLAB_LEN: usize = 16360; // MAX_ACCOUNTS=64 - haircut-ratio engine + oracle circuit breaker + policy params (no padding)
 
 #[cfg(not(feature = "test"))]
-const SLAB_LEN: usize = 992560; // MAX_ACCOUNTS=4096 - haircut-ratio engine + oracle circuit breaker (no padding)
+const SLAB_LEN: usize = 992608; // MAX_ACCOUNTS=4096 - haircut-ratio engine + oracle circuit breaker + policy params (no padding)
 
 #[cfg(feature = "test")]
 const MAX_ACCOUNTS: usize = 64;
@@ -1538,6 +1538,50 @@ fn benchmark_worst_case_scenarios() {
         }
     }
 
+    // Scenario 10: Zombie pass (zero-equity accounts ahead of the sweep cursor)
+    println!("\n━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    println!("Scenario 10: 🧟 Zombie pass after the sweep");
+    println!("  (5x longs, 30% crash wipes every user to zero equity)");
+    {
+        let test_sizes = [8, 64, 256, 512];
+
+        for &num_users in &test_sizes {
+            if num_users >= MAX_ACCOUNTS {
+                break;
+            }
+
+            let mut env = TestEnv::new();
+            env.init_market();
+
+            let lp = Keypair::new();
+            env.init_lp(&lp);
+            env.deposit(&lp, 0, 100_000_000_000_000);
+
+            let users = create_users(&mut env, num_users, 1_000_000);
+
+            // 50_000 contracts at $100 = 5M notional on 1M capital
+            for (i, user) in users.iter().enumerate() {
+                let user_idx = (i + 1) as u16;
+                env.trade(user, &lp, 0, user_idx, 50_000i128);
+            }
+
+            env.set_price(70_000_000, 200);
+
+            match env.try_crank() {
+                Ok((cu, _logs)) => {
+                    println!(
+                        "  {:>4} zombies: {:>10} CU (sweep + pass, budget 8 over 256 slots)",
+                        num_users, cu
+                    );
+                }
+                Err(_) => {
+                    println!("  {:>4} zombies: ❌ EXCEEDS 1.4M CU LIMIT", num_users);
+                    break;
+                }
+            }
+        }
+    }
+
     println!("\n━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
     println!("=== SUMMARY ===");
     println!("• Crank sweeps 256 accounts max per call (16 cranks for full 4096)");
@@ -1547,4 +1591,5 @@ fn benchmark_worst_case_scenarios() {
     );
     println!("• Key metric: worst single crank must stay under 1.4M CU");
     println!("• ADL/liquidation processing adds CU overhead per affected account");
+    println!("• Zombie pass scans at most 256 slots and closes at most 8 accounts per crank");
 }
@@ -23,8 +23,8 @@ use solana_sdk::{
 use spl_token::state::{Account as TokenAccount, AccountState};
 use std::path::PathBuf;
 
-// SLAB_LEN for production BPF (MAX_ACCOUNTS=4096) - haircut-ratio engine (no padding)
-const SLAB_LEN: usize = 992560;
+// SLAB_LEN for production BPF (MAX_ACCOUNTS=4096) - haircut-ratio engine + policy params (no padding)
+const SLAB_LEN: usize = 992608;
 const MAX_ACCOUNTS: usize = 4096;
 
 // Pyth Receiver program ID
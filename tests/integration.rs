@@ -28,7 +28,7 @@ use std::path::PathBuf;
 // Note: We use production BPF (not test feature) because test feature
 // bypasses CPI for token transfers, which fails in LiteSVM.
 // Haircut-ratio engine (ADL/socialization scratch arrays removed)
-const SLAB_LEN: usize = 992560; // MAX_ACCOUNTS=4096 + oracle circuit breaker (no padding)
+const SLAB_LEN: usize = 992608; // MAX_ACCOUNTS=4096 + oracle circuit breaker + policy params (no padding)
 const MAX_ACCOUNTS: usize = 4096;
 
 // Pyth Receiver program ID
@@ -1041,12 +1041,12 @@ impl TestEnv {
     /// Read num_used_accounts from engine state
     fn read_num_used_accounts(&self) -> u16 {
         let slab_account = self.svm.get_account(&self.slab).unwrap();
-        // ENGINE_OFF = 392 (from constants, checked via test_struct_sizes)
+        // ENGINE_OFF = 440 (from constants, checked via test_struct_sizes)
         // offset of RiskEngine.used = 408 (bitmap array)
         // used is [u64; 64] = 512 bytes
         // num_used_accounts follows used at offset 408 + 512 = 920 within RiskEngine
-        // Total offset = 392 + 920 = 1312
-        const NUM_USED_OFFSET: usize = 392 + 920; // 1312
+        // Total offset = 440 + 920 = 1360
+        const NUM_USED_OFFSET: usize = 440 + 920; // 1360
         if slab_account.data.len() < NUM_USED_OFFSET + 2 {
             return 0;
         }
@@ -1060,9 +1060,9 @@ impl TestEnv {
     /// Check if a slot is marked as used in the bitmap
     fn is_slot_used(&self, idx: u16) -> bool {
         let slab_account = self.svm.get_account(&self.slab).unwrap();
-        // ENGINE_OFF = 392, offset of RiskEngine.used = 408
-        // Bitmap is [u64; 64] at offset 392 + 408 = 800
-        const BITMAP_OFFSET: usize = 392 + 408;
+        // ENGINE_OFF = 440, offset of RiskEngine.used = 408
+        // Bitmap is [u64; 64] at offset 440 + 408 = 848
+        const BITMAP_OFFSET: usize = 440 + 408;
         let word_idx = (idx as usize) >> 6; // idx / 64
         let bit_idx = (idx as usize) & 63; // idx % 64
         let word_offset = BITMAP_OFFSET + word_idx * 8;
@@ -1080,9 +1080,9 @@ impl TestEnv {
     /// Read account capital for a slot (to verify it's zeroed after GC)
     fn read_account_capital(&self, idx: u16) -> u128 {
         let slab_account = self.svm.get_account(&self.slab).unwrap();
-        // ENGINE_OFF = 392, accounts array at offset 9136 within RiskEngine
+        // ENGINE_OFF = 440, accounts array at offset 9136 within RiskEngine
         // Account size = 240 bytes, capital at offset 8 within Account (after account_id u64)
-        const ACCOUNTS_OFFSET: usize = 392 + 9136;
+        const ACCOUNTS_OFFSET: usize = 440 + 9136;
         const ACCOUNT_SIZE: usize = 240;
         const CAPITAL_OFFSET_IN_ACCOUNT: usize = 8; // After account_id (u64)
         let account_offset =
@@ -1100,12 +1100,12 @@ impl TestEnv {
     /// Read account position_size for a slot
     fn read_account_position(&self, idx: u16) -> i128 {
         let slab_account = self.svm.get_account(&self.slab).unwrap();
-        // ENGINE_OFF = 392, accounts array at offset 9136 within RiskEngine
+        // ENGINE_OFF = 440, accounts array at offset 9136 within RiskEngine
         // Account size = 240 bytes
         // Account layout: account_id(8) + capital(16) + kind(1) + padding(7) + pnl(16) + reserved_pnl(8) +
         //                 warmup_started_at_slot(8) + warmup_slope_per_step(16) + position_size(16) + ...
         // position_size is at offset: 8 + 16 + 1 + 7 + 16 + 8 + 8 + 16 = 80
-        const ACCOUNTS_OFFSET: usize = 392 + 9136;
+        const ACCOUNTS_OFFSET: usize = 440 + 9136;
         const ACCOUNT_SIZE: usize = 240;
         const POSITION_OFFSET_IN_ACCOUNT: usize = 80;
         let account_offset =
@@ -3698,10 +3698,10 @@ impl TestEnv {
     /// Read insurance fund balance from engine
     fn read_insurance_balance(&self) -> u128 {
         let slab_account = self.svm.get_account(&self.slab).unwrap();
-        // ENGINE_OFF = 392, InsuranceFund.balance is at offset 16 within engine
+        // ENGINE_OFF = 440, InsuranceFund.balance is at offset 16 within engine
         // (vault is 16 bytes at 0, insurance_fund starts at 16)
         // InsuranceFund { balance: U128, ... } - balance is first field
-        const INSURANCE_OFFSET: usize = 392 + 16;
+        const INSURANCE_OFFSET: usize = 440 + 16;
         u128::from_le_bytes(
             slab_account.data[INSURANCE_OFFSET..INSURANCE_OFFSET + 16]
                 .try_into()
@@ -4997,10 +4997,10 @@ impl TradeCpiTestEnv {
 
     fn read_insurance_balance(&self) -> u128 {
         let slab_data = self.svm.get_account(&self.slab).unwrap().data;
-        // ENGINE_OFF = 392
+        // ENGINE_OFF = 440
         // RiskEngine layout: vault(U128=16) + insurance_fund(balance(U128=16) + fee_revenue(16))
-        // So insurance_fund.balance is at ENGINE_OFF + 16 = 408
-        const INSURANCE_BALANCE_OFFSET: usize = 392 + 16;
+        // So insurance_fund.balance is at ENGINE_OFF + 16 = 456
+        const INSURANCE_BALANCE_OFFSET: usize = 440 + 16;
         u128::from_le_bytes(
             slab_data[INSURANCE_BALANCE_OFFSET..INSURANCE_BALANCE_OFFSET + 16]
                 .try_into()
@@ -5010,9 +5010,9 @@ impl TradeCpiTestEnv {
 
     fn read_account_position(&self, idx: u16) -> i128 {
         let slab_data = self.svm.get_account(&self.slab).unwrap().data;
-        // ENGINE_OFF = 392, accounts array at offset 9136 within RiskEngine
+        // ENGINE_OFF = 440, accounts array at offset 9136 within RiskEngine
         // Account size = 240 bytes, position at offset 80 within Account
-        const ACCOUNTS_OFFSET: usize = 392 + 9136;
+        const ACCOUNTS_OFFSET: usize = 440 + 9136;
         const ACCOUNT_SIZE: usize = 240;
         const POSITION_OFFSET_IN_ACCOUNT: usize = 80;
         let account_off =
@@ -5057,21 +5057,21 @@ impl TradeCpiTestEnv {
 
     fn read_num_used_accounts(&self) -> u16 {
         let slab_data = self.svm.get_account(&self.slab).unwrap().data;
-        // ENGINE_OFF (392) + num_used offset (920) = 1312
-        u16::from_le_bytes(slab_data[1312..1314].try_into().unwrap())
+        // ENGINE_OFF (440) + num_used offset (920) = 1360
+        u16::from_le_bytes(slab_data[1360..1362].try_into().unwrap())
     }
 
     /// Read pnl_pos_tot aggregate from slab
     /// This is the sum of all positive PnL values, used for haircut calculations
     fn read_pnl_pos_tot(&self) -> u128 {
         let slab_data = self.svm.get_account(&self.slab).unwrap().data;
-        // ENGINE_OFF = 392
+        // ENGINE_OFF = 440
         // RiskEngine layout: vault(16) + insurance_fund(32) + params(144) +
         //   current_slot(8) + funding_index(16) + last_funding_slot(8) +
         //   funding_rate_bps(8) + last_crank_slot(8) + max_crank_staleness(8) +
         //   total_open_interest(16) + c_tot(16) + pnl_pos_tot(16)
         // Offset: 16+32+144+8+16+8+8+8+8+16+16 = 280
-        const PNL_POS_TOT_OFFSET: usize = 392 + 280;
+        const PNL_POS_TOT_OFFSET: usize = 440 + 280;
         u128::from_le_bytes(
             slab_data[PNL_POS_TOT_OFFSET..PNL_POS_TOT_OFFSET + 16]
                 .try_into()
@@ -5083,7 +5083,7 @@ impl TradeCpiTestEnv {
     fn read_c_tot(&self) -> u128 {
         let slab_data = self.svm.get_account(&self.slab).unwrap().data;
         // c_tot is at offset 264 within RiskEngine (16 bytes before pnl_pos_tot)
-        const C_TOT_OFFSET: usize = 392 + 264;
+        const C_TOT_OFFSET: usize = 440 + 264;
         u128::from_le_bytes(
             slab_data[C_TOT_OFFSET..C_TOT_OFFSET + 16]
                 .try_into()
@@ -5095,7 +5095,7 @@ impl TradeCpiTestEnv {
     fn read_vault(&self) -> u128 {
         let slab_data = self.svm.get_account(&self.slab).unwrap().data;
         // vault is at offset 0 within RiskEngine
-        const VAULT_OFFSET: usize = 392;
+        const VAULT_OFFSET: usize = 440;
         u128::from_le_bytes(
             slab_data[VAULT_OFFSET..VAULT_OFFSET + 16]
                 .try_into()
@@ -5115,7 +5115,7 @@ impl TradeCpiTestEnv {
         //   warmup_started_at_slot: u64 (8), offset 56
         //   warmup_slope_per_step: U128 (16), offset 64
         //   position_size: I128 (16), offset 80 (confirmed in other tests)
-        const ACCOUNTS_OFFSET: usize = 392 + 9136;
+        const ACCOUNTS_OFFSET: usize = 440 + 9136;
         const ACCOUNT_SIZE: usize = 240;
         const PNL_OFFSET_IN_ACCOUNT: usize = 32; // pnl is at offset 32 within Account
         let account_off = ACCOUNTS_OFFSET + (idx as usize) * ACCOUNT_SIZE + PNL_OFFSET_IN_ACCOUNT;
@@ -5271,7 +5271,7 @@ impl TradeCpiTestEnv {
 
     fn read_account_capital(&self, idx: u16) -> u128 {
         let slab_data = self.svm.get_account(&self.slab).unwrap().data;
-        const ACCOUNTS_OFFSET: usize = 392 + 9136;
+        const ACCOUNTS_OFFSET: usize = 440 + 9136;
         const ACCOUNT_SIZE: usize = 240;
         const CAPITAL_OFFSET_IN_ACCOUNT: usize = 8;
         let account_off =
@@ -7621,7 +7621,7 @@ impl TestEnv {
     /// Read c_tot aggregate from slab
     fn read_c_tot(&self) -> u128 {
         let slab_data = self.svm.get_account(&self.slab).unwrap().data;
-        const C_TOT_OFFSET: usize = 392 + 264;
+        const C_TOT_OFFSET: usize = 440 + 264;
         u128::from_le_bytes(
             slab_data[C_TOT_OFFSET..C_TOT_OFFSET + 16]
                 .try_into()
@@ -7632,7 +7632,7 @@ impl TestEnv {
     /// Read vault balance from engine state
     fn read_engine_vault(&self) -> u128 {
         let slab_data = self.svm.get_account(&self.slab).unwrap().data;
-        const VAULT_OFFSET: usize = 392;
+        const VAULT_OFFSET: usize = 440;
         u128::from_le_bytes(
             slab_data[VAULT_OFFSET..VAULT_OFFSET + 16]
                 .try_into()
@@ -7643,7 +7643,7 @@ impl TestEnv {
     /// Read pnl_pos_tot aggregate from slab
     fn read_pnl_pos_tot(&self) -> u128 {
         let slab_data = self.svm.get_account(&self.slab).unwrap().data;
-        const PNL_POS_TOT_OFFSET: usize = 392 + 280;
+        const PNL_POS_TOT_OFFSET: usize = 440 + 280;
         u128::from_le_bytes(
             slab_data[PNL_POS_TOT_OFFSET..PNL_POS_TOT_OFFSET + 16]
                 .try_into()
@@ -7654,7 +7654,7 @@ impl TestEnv {
     /// Read account PnL for a slot
     fn read_account_pnl(&self, idx: u16) -> i128 {
         let slab_data = self.svm.get_account(&self.slab).unwrap().data;
-        const ACCOUNTS_OFFSET: usize = 392 + 9136;
+        const ACCOUNTS_OFFSET: usize = 440 + 9136;
         const ACCOUNT_SIZE: usize = 240;
         const PNL_OFFSET_IN_ACCOUNT: usize = 32;
         let account_off = ACCOUNTS_OFFSET + (idx as usize) * ACCOUNT_SIZE + PNL_OFFSET_IN_ACCOUNT;
@@ -13434,7 +13434,7 @@ fn test_attack_hyperp_same_slot_crank_no_index_movement() {
     // Config offset: header is 16 bytes, config starts after that
     // last_effective_price_e6 offset within config (check source for exact layout)
     // Read last_effective_price_e6 (the index) before same-slot crank
-    // It's the last u64 in the config section: slab bytes [384..392]
+    // It's at config offset 312 (HEADER_LEN 72 + 312): slab bytes [384..392]
     let slab_before = env.svm.get_account(&env.slab).unwrap().data;
     const INDEX_OFF: usize = 384;
     let index_before =
@@ -15003,7 +15003,7 @@ fn test_attack_funding_anti_retroactivity_zero_dt() {
     // Engine vault should still be correct
     let engine_vault = {
         let slab = env.svm.get_account(&env.slab).unwrap();
-        u128::from_le_bytes(slab.data[392..408].try_into().unwrap())
+        u128::from_le_bytes(slab.data[440..456].try_into().unwrap())
     };
     assert!(engine_vault > 0, "Engine vault should be positive");
 }
@@ -15050,7 +15050,7 @@ fn test_attack_withdrawal_with_warmup_settlement() {
     };
     let engine_vault = {
         let slab = env.svm.get_account(&env.slab).unwrap();
-        u128::from_le_bytes(slab.data[392..408].try_into().unwrap())
+        u128::from_le_bytes(slab.data[440..456].try_into().unwrap())
     };
 
     // Key assertion: SPL vault >= engine vault always
@@ -15224,7 +15224,7 @@ fn test_attack_multi_crank_funding_conservation() {
     // Engine vault should still be total deposited amount
     let engine_vault = {
         let slab = env.svm.get_account(&env.slab).unwrap();
-        u128::from_le_bytes(slab.data[392..408].try_into().unwrap())
+        u128::from_le_bytes(slab.data[440..456].try_into().unwrap())
     };
     assert_eq!(
         engine_vault, 20_000_000_000,
@@ -15321,7 +15321,7 @@ fn test_attack_updateconfig_preserves_conservation() {
     };
     let engine_vault_before = {
         let slab = env.svm.get_account(&env.slab).unwrap();
-        u128::from_le_bytes(slab.data[392..408].try_into().unwrap())
+        u128::from_le_bytes(slab.data[440..456].try_into().unwrap())
     };
 
     // UpdateConfig with different parameters
@@ -15346,7 +15346,7 @@ fn test_attack_updateconfig_preserves_conservation() {
     };
     let engine_vault_after = {
         let slab = env.svm.get_account(&env.slab).unwrap();
-        u128::from_le_bytes(slab.data[392..408].try_into().unwrap())
+        u128::from_le_bytes(slab.data[440..456].try_into().unwrap())
     };
 
     // Conservation: UpdateConfig must not change vault balances
@@ -28866,3 +28866,79 @@ fn test_honest_participants_standard_market_full_lifecycle() {
 
     println!("HONEST PARTICIPANTS STANDARD MARKET FULL LIFECYCLE: PASSED");
 }
+
+// ============================================================================
+// Slab layout migration (version 1 -> 2)
+// ============================================================================
+
+/// A slab in the version-1 layout (MarketConfig without the wrapper policy
+/// block) is rejected until MigrateSlab grows it in place. Afterwards it is
+/// byte-for-byte the current layout, positions intact, and keeps cranking.
+#[test]
+fn test_migrate_v1_slab_round_trip() {
+    let path = program_path();
+    if !path.exists() {
+        println!("SKIP: BPF not found");
+        return;
+    }
+
+    let mut env = TestEnv::new();
+    env.init_market_with_invert(0);
+
+    let lp = Keypair::new();
+    let lp_idx = env.init_lp(&lp);
+    env.deposit(&lp, lp_idx, 100_000_000_000);
+
+    let user = Keypair::new();
+    let user_idx = env.init_user(&user);
+    env.deposit(&user, user_idx, 10_000_000_000);
+
+    let size: i128 = 100_000;
+    env.trade(&user, &lp, lp_idx, user_idx, size);
+
+    // SBF layout: header 72 + v1 config 320, then the 48-byte policy block
+    const V1_CONFIG_END: usize = 72 + 320;
+    const V1_ENGINE_OFF: usize = 392;
+    const ENGINE_OFF: usize = 440;
+
+    let current = env.svm.get_account(&env.slab).unwrap();
+    let mut v1 = current.data[..V1_CONFIG_END].to_vec();
+    v1.resize(V1_ENGINE_OFF, 0);
+    v1.extend_from_slice(&current.data[ENGINE_OFF..]);
+    v1[8..12].copy_from_slice(&1u32.to_le_bytes()); // header.version
+    assert_eq!(v1.len(), SLAB_LEN - (ENGINE_OFF - V1_ENGINE_OFF));
+    // The grow must stay rent-exempt, so fund the slab for the new length
+    let lamports = solana_sdk::rent::Rent::default().minimum_balance(SLAB_LEN);
+    env.svm
+        .set_account(
+            env.slab,
+            Account {
+                lamports,
+                data: v1,
+                ..current.clone()
+            },
+        )
+        .unwrap();
+
+    assert!(env.try_crank().is_err(), "v1 slab must be rejected before migration");
+
+    let ix = Instruction {
+        program_id: env.program_id,
+        accounts: vec![AccountMeta::new(env.slab, false)],
+        data: vec![33u8], // MigrateSlab
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&env.payer.pubkey()),
+        &[&env.payer],
+        env.svm.latest_blockhash(),
+    );
+    env.svm.send_transaction(tx).expect("MigrateSlab failed");
+
+    let migrated = env.svm.get_account(&env.slab).unwrap();
+    assert_eq!(migrated.data, current.data, "migrated slab must match current layout");
+    assert_eq!(env.read_account_position(user_idx), size);
+
+    env.set_slot(200);
+    env.crank();
+}
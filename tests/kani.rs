@@ -28,12 +28,19 @@ use percolator_prog::matcher_abi::{
 use percolator_prog::oracle::clamp_toward_with_dt;
 use percolator_prog::verify::{
     abi_ok,
+    account_deficit,
     // New: Dust math
     accumulate_dust,
     admin_ok,
     // New: Unit scale conversion math
     base_to_units,
+    below_margin_mtm,
+    carry_per_slot,
+    close_at_mark,
+    concentration_bps,
     cpi_trade_size,
+    crank_deadman_blocks,
+    crank_interval,
     decide_admin_op,
     decide_crank,
     // New: allow_panic crank decision
@@ -43,20 +50,42 @@ use percolator_prog::verify::{
     decide_trade_cpi_from_ret,
     decide_trade_nocpi,
     decision_nonce,
+    entry_deviation_exceeds,
+    equity_mtm,
+    free_equity,
     gate_active,
     // New: InitMarket scale validation
     init_market_scale_ok,
+    insolvency_move_bps,
+    insurance_ratio_blocks_withdraw,
+    insurance_runway_slots,
     // New: Oracle inversion math
     invert_price_e6,
+    is_zombie,
     len_ok,
+    liquidation_fee,
+    liquidation_limit_ok,
+    liquidation_price,
     lp_pda_shape_ok,
+    margin_health,
+    margin_params_ok,
+    margin_required,
+    mark_pnl,
     matcher_identity_ok,
     matcher_shape_ok,
+    max_position_for_collateral,
+    min_deposit_blocks,
+    newly_liquidatable,
     nonce_on_failure,
     nonce_on_success,
+    open_size_affordable,
     oracle_feed_id_ok,
+    orphaned_vault,
+    owner_account_limit_reached,
     owner_ok,
     pda_key_matches,
+    pnl_claim_at_mark,
+    risk_contribution,
     // New: Oracle unit scale math
     scale_price_e6,
     // Account validation helpers
@@ -64,11 +93,19 @@ use percolator_prog::verify::{
     // Decision helpers for program-level coupling proofs
     single_owner_authorized,
     slab_shape_ok,
+    slots_to_full_warmup,
     sweep_dust,
+    system_leverage_ok,
     trade_authorized,
+    trade_fee,
+    trade_size_ok,
+    trial_balance_residual,
     units_to_base,
+    warmed_pnl,
+    wind_down_payout,
     // New: Withdraw alignment
     withdraw_amount_aligned,
+    withdrawal_honorable,
     writable_ok,
     LpPdaShape,
     MatcherAccountsShape,
@@ -3428,3 +3465,1072 @@ fn kani_clamp_toward_formula_concrete() {
         "result must equal mark.clamp(990_000, 1_010_000)"
     );
 }
+
+// =============================================================================
+// LIQUIDATION FEE PREVIEW PROOFS (liquidation_fee)
+// =============================================================================
+//
+// liquidation_fee_for() previews the fee liquidate_at_oracle charges. These
+// prove the shared pure formula is monotone in close size, never rounds a
+// real close down to zero, and is exact ceil(notional * bps / 10_000) below
+// the cap.
+
+/// Prove: A larger close never pays a smaller fee (for a fixed price, bps
+/// and cap), so splitting a liquidation cannot raise the per-unit charge.
+#[kani::proof]
+fn kani_liquidation_fee_monotone_in_size() {
+    let a: u128 = kani::any();
+    let b: u128 = kani::any();
+    let price: u64 = kani::any();
+    let fee_bps: u64 = kani::any();
+    let fee_cap: u128 = kani::any();
+
+    kani::assume(a <= b && b <= KANI_MAX_QUOTIENT as u128);
+    kani::assume(price <= 10_000_000);
+    kani::assume(fee_bps <= 10_000);
+
+    assert!(
+        liquidation_fee(a, price, fee_bps, fee_cap) <= liquidation_fee(b, price, fee_bps, fee_cap)
+    );
+}
+
+/// Prove: Any nonzero notional with a nonzero rate and cap pays at least
+/// one unit, so dust-sized closes cannot round the fee away.
+#[kani::proof]
+fn kani_liquidation_fee_nonzero_for_nonzero_notional() {
+    let close_abs: u128 = kani::any();
+    let price: u64 = kani::any();
+    let fee_bps: u64 = kani::any();
+    let fee_cap: u128 = kani::any();
+
+    kani::assume(close_abs <= KANI_MAX_QUOTIENT as u128);
+    kani::assume(price <= 10_000_000);
+    kani::assume(fee_bps > 0 && fee_bps <= 10_000);
+    kani::assume(fee_cap > 0);
+    kani::assume(close_abs * price as u128 >= 1_000_000);
+
+    assert!(liquidation_fee(close_abs, price, fee_bps, fee_cap) > 0);
+}
+
+/// Prove: Below the cap the fee is exactly ceil(notional * bps / 10_000).
+/// Uses a concrete price to keep the division tractable.
+#[kani::proof]
+fn kani_liquidation_fee_formula_uncapped() {
+    let close_abs: u128 = kani::any();
+    let fee_bps: u64 = kani::any();
+    kani::assume(close_abs <= KANI_MAX_QUOTIENT as u128);
+    kani::assume(fee_bps > 0 && fee_bps <= 10_000);
+
+    // price = 2.0 (e6) => notional = 2 * close_abs
+    let price: u64 = 2_000_000;
+    let notional = close_abs * 2;
+    let fee = liquidation_fee(close_abs, price, fee_bps, u128::MAX);
+
+    if notional == 0 {
+        assert_eq!(fee, 0);
+    } else {
+        // fee is the smallest value with fee * 10_000 >= notional * bps
+        assert!(fee * 10_000 >= notional * fee_bps as u128);
+        assert!((fee - 1) * 10_000 < notional * fee_bps as u128);
+    }
+}
+
+// =============================================================================
+// RISK CONTRIBUTION PROOFS (risk_contribution)
+// =============================================================================
+
+/// Prove: A flat account contributes zero risk.
+#[kani::proof]
+fn kani_risk_contribution_flat_is_zero() {
+    let total_oi: u128 = kani::any();
+    let price: u64 = kani::any();
+
+    assert_eq!(risk_contribution(0, total_oi, price), 0);
+}
+
+/// Prove: Contribution is between notional and 2x notional, so the sum over
+/// all accounts is bounded by 2 * total_open_interest notional.
+#[kani::proof]
+fn kani_risk_contribution_bounded_by_twice_notional() {
+    let abs_pos: u128 = kani::any();
+    let total_oi: u128 = kani::any();
+    kani::assume(abs_pos <= KANI_MAX_QUOTIENT as u128);
+    kani::assume(total_oi >= abs_pos && total_oi <= KANI_MAX_QUOTIENT as u128);
+
+    // price = 1.0 (e6) => notional = abs_pos
+    let c = risk_contribution(abs_pos, total_oi, 1_000_000);
+    assert!(c >= abs_pos, "contribution must cover notional");
+    assert!(c <= 2 * abs_pos, "contribution must be at most 2x notional");
+    assert!(c <= 2 * total_oi, "contribution bounded by 2x OI notional");
+}
+
+// =============================================================================
+// MIN INSURANCE RATIO WITHDRAWAL GATE (insurance_ratio_blocks_withdraw)
+// =============================================================================
+
+/// Prove: min_bps == 0 disables the gate entirely.
+#[kani::proof]
+fn kani_insurance_ratio_gate_disabled_when_zero() {
+    let insurance: u128 = kani::any();
+    let total_oi: u128 = kani::any();
+
+    assert!(!insurance_ratio_blocks_withdraw(insurance, total_oi, 0));
+}
+
+/// Prove: Below the threshold a withdrawal is blocked, and it stays blocked
+/// for any insurance balance that has not grown (only a top-up can clear it).
+#[kani::proof]
+fn kani_insurance_ratio_gate_blocks_until_topped_up() {
+    let insurance: u128 = kani::any();
+    let later_insurance: u128 = kani::any();
+    let total_oi: u128 = kani::any();
+    let min_bps: u64 = kani::any();
+    kani::assume(insurance <= KANI_MAX_QUOTIENT as u128);
+    kani::assume(total_oi > 0 && total_oi <= KANI_MAX_QUOTIENT as u128);
+    kani::assume(min_bps > 0 && min_bps <= 10_000);
+
+    kani::assume(insurance * 10_000 < total_oi * min_bps as u128);
+    assert!(insurance_ratio_blocks_withdraw(
+        insurance, total_oi, min_bps
+    ));
+
+    kani::assume(later_insurance <= insurance);
+    assert!(insurance_ratio_blocks_withdraw(
+        later_insurance,
+        total_oi,
+        min_bps
+    ));
+}
+
+/// Prove: At or above the threshold withdrawals are not blocked.
+#[kani::proof]
+fn kani_insurance_ratio_gate_allows_when_met() {
+    let insurance: u128 = kani::any();
+    let total_oi: u128 = kani::any();
+    let min_bps: u64 = kani::any();
+    kani::assume(insurance <= KANI_MAX_QUOTIENT as u128);
+    kani::assume(total_oi > 0 && total_oi <= KANI_MAX_QUOTIENT as u128);
+    kani::assume(min_bps <= 10_000);
+
+    kani::assume(insurance * 10_000 >= total_oi * min_bps as u128);
+    assert!(!insurance_ratio_blocks_withdraw(
+        insurance, total_oi, min_bps
+    ));
+}
+
+// =============================================================================
+// INSOLVENCY STRESS MOVE (insolvency_move_bps)
+// =============================================================================
+
+/// Prove: A perfectly balanced book (net skew == 0) reports no insolvency move.
+#[kani::proof]
+fn kani_insolvency_move_none_when_balanced() {
+    let price: u64 = kani::any();
+    let buffer: u128 = kani::any();
+
+    assert!(insolvency_move_bps(0, price, buffer).is_none());
+}
+
+/// Prove: With a nonzero skew, the reported move is enough to consume the buffer.
+/// Uses price = 1.0 (e6) so loss at move m bps = net_abs * m / 10_000.
+#[kani::proof]
+fn kani_insolvency_move_consumes_buffer() {
+    let net_abs: u128 = kani::any();
+    let buffer: u128 = kani::any();
+    kani::assume(net_abs > 0 && net_abs <= KANI_MAX_QUOTIENT as u128);
+    kani::assume(buffer <= KANI_MAX_QUOTIENT as u128);
+
+    let bps = insolvency_move_bps(net_abs, 1_000_000, buffer).unwrap() as u128;
+    assert!(net_abs * bps >= buffer * 10_000, "move must exhaust buffer");
+    if bps > 0 {
+        assert!(
+            net_abs * (bps - 1) < buffer * 10_000,
+            "move must be minimal"
+        );
+    }
+}
+
+// =============================================================================
+// TRIAL BALANCE IDENTITY (trial_balance_residual)
+// =============================================================================
+
+/// Prove: vault == c_tot + insurance + residual for token-backed balances.
+#[kani::proof]
+fn kani_trial_balance_identity() {
+    let vault: u128 = kani::any();
+    let c_tot: u128 = kani::any();
+    let insurance: u128 = kani::any();
+    kani::assume(vault <= u64::MAX as u128);
+    kani::assume(c_tot <= u64::MAX as u128);
+    kani::assume(insurance <= u64::MAX as u128);
+
+    let residual = trial_balance_residual(vault, c_tot, insurance);
+    assert_eq!(vault as i128, c_tot as i128 + insurance as i128 + residual);
+}
+
+/// Prove: residual >= 0 whenever the accounting invariant
+/// vault >= c_tot + insurance holds.
+#[kani::proof]
+fn kani_trial_balance_residual_nonneg_under_accounting_inv() {
+    let vault: u128 = kani::any();
+    let c_tot: u128 = kani::any();
+    let insurance: u128 = kani::any();
+    kani::assume(vault <= u64::MAX as u128);
+    kani::assume(c_tot <= u64::MAX as u128);
+    kani::assume(insurance <= u64::MAX as u128);
+    kani::assume(vault >= c_tot + insurance);
+
+    assert!(trial_balance_residual(vault, c_tot, insurance) >= 0);
+}
+
+// =============================================================================
+// ORPHANED VAULT SWEEP (orphaned_vault)
+// =============================================================================
+
+/// Prove: Crediting the orphaned amount to insurance leaves nothing orphaned
+/// and keeps vault >= c_tot + insurance (vault itself is untouched).
+#[kani::proof]
+fn kani_orphaned_vault_zero_after_sweep() {
+    let vault: u128 = kani::any();
+    let c_tot: u128 = kani::any();
+    let insurance: u128 = kani::any();
+    let pnl_pos_tot: u128 = kani::any();
+    kani::assume(vault <= u64::MAX as u128);
+    kani::assume(c_tot <= u64::MAX as u128);
+    kani::assume(insurance <= u64::MAX as u128);
+    kani::assume(pnl_pos_tot <= u64::MAX as u128);
+    kani::assume(vault >= c_tot + insurance);
+
+    let orphan = orphaned_vault(vault, c_tot, insurance, pnl_pos_tot);
+    let new_insurance = insurance + orphan;
+
+    assert_eq!(orphaned_vault(vault, c_tot, new_insurance, pnl_pos_tot), 0);
+    assert!(vault >= c_tot + new_insurance, "conservation must hold");
+}
+
+// =============================================================================
+// MARGIN CHANGE RE-CHECK (below_margin_mtm, margin_params_ok)
+// =============================================================================
+
+/// Prove: Flat accounts are never reported as below margin.
+#[kani::proof]
+fn kani_below_margin_flat_never_listed() {
+    let capital: u128 = kani::any();
+    let pnl: i128 = kani::any();
+    let entry: u64 = kani::any();
+    let price: u64 = kani::any();
+    let bps: u64 = kani::any();
+
+    assert!(!below_margin_mtm(capital, pnl, 0, entry, price, bps));
+}
+
+/// Prove: An account is listed iff its production MTM equity is at or below
+/// the production margin requirement (no false positives or negatives).
+#[kani::proof]
+fn kani_below_margin_matches_production_formula() {
+    let capital: u128 = kani::any();
+    let pnl: i128 = kani::any();
+    let pos: i128 = kani::any();
+    let entry: u64 = kani::any();
+    let bps: u64 = kani::any();
+    kani::assume(capital <= 1_000_000);
+    kani::assume(pnl > -1_000_000 && pnl < 1_000_000);
+    kani::assume(pos != 0 && pos > -1_000 && pos < 1_000);
+    kani::assume(entry > 0 && entry <= 2_000_000);
+    kani::assume(bps <= 10_000);
+    let price: u64 = 1_000_000;
+
+    let mark = production_mark_pnl(pos, entry, price).unwrap();
+    let equity = production_equity(capital, pnl, mark);
+    let required = production_margin_required(production_position_value(pos, price), bps);
+
+    assert_eq!(
+        below_margin_mtm(capital, pnl, pos, entry, price, bps),
+        equity <= required
+    );
+}
+
+/// Prove: Margin params are accepted only with 0 < maintenance <= initial <= 100%.
+#[kani::proof]
+fn kani_margin_params_ok_requires_ordering() {
+    let initial: u64 = kani::any();
+    let maintenance: u64 = kani::any();
+
+    assert!(!margin_params_ok(initial, 0));
+    if margin_params_ok(initial, maintenance) {
+        assert!(maintenance > 0);
+        assert!(initial >= maintenance);
+        assert!(initial <= 10_000);
+    }
+}
+
+// =============================================================================
+// TRADE SIZE i128::MIN GUARD (trade_size_ok)
+// =============================================================================
+
+/// Prove: i128::MIN is rejected and every accepted size negates safely.
+#[kani::proof]
+fn kani_trade_size_ok_rejects_i128_min() {
+    let size: i128 = kani::any();
+
+    assert!(!trade_size_ok(i128::MIN));
+    if trade_size_ok(size) {
+        assert!(size.checked_neg().is_some(), "LP delta must not overflow");
+    }
+}
+
+// =============================================================================
+// LIQUIDATION PRICE BOUNDARY (liquidation_price)
+// =============================================================================
+
+/// Prove: At the returned price the account is not above maintenance margin,
+/// and one tick in the safe direction it is. Small price range keeps the
+/// binary search unwinding tractable.
+#[kani::proof]
+#[kani::unwind(10)]
+fn kani_liquidation_price_is_exact_boundary() {
+    let capital: u128 = kani::any();
+    let pnl: i128 = kani::any();
+    let pos: i128 = kani::any();
+    let entry: u64 = kani::any();
+    let mm_bps: u64 = kani::any();
+    kani::assume(capital <= 1_000);
+    kani::assume(pnl > -1_000 && pnl < 1_000);
+    kani::assume(pos != 0 && pos > -4_000_000 && pos < 4_000_000);
+    kani::assume(entry <= 256);
+    kani::assume(mm_bps <= 10_000);
+    let max_price: u64 = 256;
+
+    if let Some(p) = liquidation_price(capital, pnl, pos, entry, mm_bps, max_price) {
+        assert!(below_margin_mtm(capital, pnl, pos, entry, p, mm_bps));
+        let safer = if pos > 0 { p + 1 } else { p - 1 };
+        assert!(!below_margin_mtm(capital, pnl, pos, entry, safer, mm_bps));
+    }
+}
+
+/// Prove: Flat accounts have no liquidation price.
+#[kani::proof]
+fn kani_liquidation_price_none_when_flat() {
+    let capital: u128 = kani::any();
+    let pnl: i128 = kani::any();
+    let entry: u64 = kani::any();
+    let mm_bps: u64 = kani::any();
+    let max_price: u64 = kani::any();
+
+    assert!(liquidation_price(capital, pnl, 0, entry, mm_bps, max_price).is_none());
+}
+
+// =============================================================================
+// SYSTEM-LEVEL WITHDRAWAL BACKING (withdrawal_honorable)
+// =============================================================================
+
+/// Prove: withdrawal_honorable is false exactly when the payout exceeds
+/// post-settle capital, would dip into the vault backing of other accounts'
+/// capital or insurance, or insurance breaches the risk-reduction floor.
+#[kani::proof]
+fn kani_withdrawal_honorable_characterization() {
+    let vault: u128 = kani::any();
+    let c_tot: u128 = kani::any();
+    let insurance: u128 = kani::any();
+    let capital: u128 = kani::any();
+    let pnl: i128 = kani::any();
+    let amount: u128 = kani::any();
+    let threshold: u128 = kani::any();
+    kani::assume(capital <= c_tot);
+    kani::assume(c_tot <= u64::MAX as u128);
+    kani::assume(insurance <= u64::MAX as u128);
+    kani::assume(vault <= u64::MAX as u128);
+    kani::assume(pnl > -(u64::MAX as i128) && pnl < u64::MAX as i128);
+
+    let ok = withdrawal_honorable(vault, c_tot, insurance, capital, pnl, amount, threshold);
+
+    let loss = if pnl < 0 { (-pnl) as u128 } else { 0 };
+    let settled = if capital > loss { capital - loss } else { 0 };
+    let expected = amount <= settled
+        && amount <= vault
+        && vault - amount >= (c_tot - capital) + insurance
+        && !gate_active(threshold, insurance);
+    assert_eq!(ok, expected);
+}
+
+/// Prove: The check depends on the amount: if a payout is honorable, every
+/// smaller payout from the same state is too.
+#[kani::proof]
+fn kani_withdrawal_honorable_monotone_in_amount() {
+    let vault: u128 = kani::any();
+    let c_tot: u128 = kani::any();
+    let insurance: u128 = kani::any();
+    let capital: u128 = kani::any();
+    let pnl: i128 = kani::any();
+    let amount: u128 = kani::any();
+    let smaller: u128 = kani::any();
+    let threshold: u128 = kani::any();
+    kani::assume(smaller <= amount);
+
+    if withdrawal_honorable(vault, c_tot, insurance, capital, pnl, amount, threshold) {
+        assert!(withdrawal_honorable(
+            vault, c_tot, insurance, capital, pnl, smaller, threshold
+        ));
+    }
+}
+
+/// Prove: An honorable withdrawal leaves the other accounts' capital and
+/// insurance fully backed by the vault, and never pays out more than the
+/// account holds after its losses are settled.
+#[kani::proof]
+fn kani_withdrawal_honorable_preserves_other_claims() {
+    let vault: u128 = kani::any();
+    let c_tot: u128 = kani::any();
+    let insurance: u128 = kani::any();
+    let capital: u128 = kani::any();
+    let pnl: i128 = kani::any();
+    let amount: u128 = kani::any();
+    let threshold: u128 = kani::any();
+
+    if withdrawal_honorable(vault, c_tot, insurance, capital, pnl, amount, threshold) {
+        assert!(vault - amount >= (c_tot - capital) + insurance);
+        assert!(amount <= capital);
+        if pnl < 0 {
+            assert!(amount <= capital - pnl.unsigned_abs());
+        }
+        assert!(!gate_active(threshold, insurance));
+    }
+}
+
+// =============================================================================
+// INSURANCE RUNWAY (insurance_runway_slots)
+// =============================================================================
+
+/// Prove: Zero loss rate yields an unbounded runway.
+#[kani::proof]
+fn kani_insurance_runway_zero_loss_is_max() {
+    let spendable: u128 = kani::any();
+    assert_eq!(insurance_runway_slots(spendable, 0), u64::MAX);
+}
+
+/// Prove: For fixed insurance, a higher loss rate never lengthens the runway.
+#[kani::proof]
+fn kani_insurance_runway_monotone_in_loss() {
+    let spendable: u128 = kani::any();
+    let loss_a: u128 = kani::any();
+    let loss_b: u128 = kani::any();
+    kani::assume(spendable <= KANI_MAX_QUOTIENT as u128 * 16);
+    kani::assume(loss_a <= loss_b);
+    kani::assume(loss_b <= KANI_MAX_QUOTIENT as u128);
+
+    assert!(insurance_runway_slots(spendable, loss_b) <= insurance_runway_slots(spendable, loss_a));
+}
+
+// =============================================================================
+// ZOMBIE ACCOUNTS (is_zombie)
+// =============================================================================
+
+/// Prove: A zombie is below maintenance margin for any margin setting, so
+/// liquidation always triggers.
+#[kani::proof]
+fn kani_zombie_is_below_maintenance() {
+    let capital: u128 = kani::any();
+    let pnl: i128 = kani::any();
+    let pos: i128 = kani::any();
+    let entry: u64 = kani::any();
+    let price: u64 = kani::any();
+    let mm_bps: u64 = kani::any();
+    kani::assume(capital <= KANI_MAX_QUOTIENT as u128);
+    kani::assume(pnl > -(KANI_MAX_QUOTIENT as i128) && pnl < KANI_MAX_QUOTIENT as i128);
+    kani::assume(pos > -(KANI_MAX_QUOTIENT as i128) && pos < KANI_MAX_QUOTIENT as i128);
+    kani::assume(entry <= KANI_MAX_QUOTIENT && price <= KANI_MAX_QUOTIENT);
+
+    if is_zombie(capital, pnl, pos, entry, price) {
+        assert!(below_margin_mtm(capital, pnl, pos, entry, price, mm_bps));
+    }
+}
+
+/// Prove: A flat account (e.g. liquidated to zero) is never a zombie.
+#[kani::proof]
+fn kani_zombie_false_when_flat() {
+    let capital: u128 = kani::any();
+    let pnl: i128 = kani::any();
+    let entry: u64 = kani::any();
+    let price: u64 = kani::any();
+
+    assert!(!is_zombie(capital, pnl, 0, entry, price));
+}
+
+/// Prove: Mark overflow never flags a zombie, so the crank's zombie pass
+/// cannot pick a possibly healthy account on arithmetic overflow alone.
+#[kani::proof]
+fn kani_zombie_false_on_mark_overflow() {
+    let capital: u128 = kani::any();
+    let pnl: i128 = kani::any();
+    let pos: i128 = kani::any();
+    let entry: u64 = kani::any();
+    let price: u64 = kani::any();
+
+    if mark_pnl(pos, entry, price).is_none() {
+        assert!(!is_zombie(capital, pnl, pos, entry, price));
+    }
+}
+
+// =============================================================================
+// MAX POSITION FOR COLLATERAL (max_position_for_collateral)
+// =============================================================================
+
+/// Prove: The returned size is affordable and one unit larger is not
+/// (unless capped at max_abs). Small ranges keep the search tractable.
+#[kani::proof]
+#[kani::unwind(10)]
+fn kani_max_position_for_collateral_is_exact() {
+    let collateral: u128 = kani::any();
+    let price: u64 = kani::any();
+    let im_bps: u64 = kani::any();
+    let fee_bps: u64 = kani::any();
+    kani::assume(collateral <= 1_000);
+    kani::assume(price >= 1 && price <= 1_000_000);
+    kani::assume(im_bps <= 10_000);
+    kani::assume(fee_bps <= 1_000);
+    let max_abs: u128 = 256;
+
+    let s = max_position_for_collateral(collateral, price, im_bps, fee_bps, max_abs);
+    assert!(s <= max_abs);
+    if s > 0 {
+        assert!(open_size_affordable(collateral, s, price, im_bps, fee_bps));
+    }
+    if s < max_abs {
+        assert!(!open_size_affordable(
+            collateral,
+            s + 1,
+            price,
+            im_bps,
+            fee_bps
+        ));
+    }
+}
+
+// =============================================================================
+// STALE MARK DETECTION (entry_deviation_exceeds)
+// =============================================================================
+
+/// Prove: An account is flagged exactly when its entry deviates from the
+/// oracle by more than threshold_bps (exact rational comparison).
+#[kani::proof]
+fn kani_entry_deviation_exceeds_exact() {
+    let entry: u64 = kani::any();
+    let price: u64 = kani::any();
+    let thr: u16 = kani::any();
+
+    let flagged = entry_deviation_exceeds(entry, price, thr);
+    let diff = if price > entry {
+        price - entry
+    } else {
+        entry - price
+    } as u128;
+    assert_eq!(flagged, diff * 10_000 > thr as u128 * entry as u128);
+    if diff == 0 {
+        assert!(!flagged, "no deviation is never stale");
+    }
+}
+
+// =============================================================================
+// REQUIRED VAULT (pnl_claim_at_mark)
+// =============================================================================
+
+/// Prove: If the vault covers c_tot + insurance + the account's claim, the
+/// account can close and withdraw capital + claim and the remaining vault
+/// still covers the remaining capital plus insurance.
+#[kani::proof]
+fn kani_required_vault_covers_full_exit() {
+    let vault: u128 = kani::any();
+    let c_tot: u128 = kani::any();
+    let insurance: u128 = kani::any();
+    let capital: u128 = kani::any();
+    let pnl: i128 = kani::any();
+    let mark: i128 = kani::any();
+    kani::assume(capital <= c_tot);
+    kani::assume(c_tot <= u64::MAX as u128 && insurance <= u64::MAX as u128);
+    kani::assume(pnl > i64::MIN as i128 && pnl < i64::MAX as i128);
+    kani::assume(mark > i64::MIN as i128 && mark < i64::MAX as i128);
+
+    let claim = pnl_claim_at_mark(pnl, mark);
+    if vault >= c_tot + insurance + claim {
+        let payout = capital + claim;
+        assert!(vault >= payout);
+        assert!(vault - payout >= (c_tot - capital) + insurance);
+    }
+}
+
+// =============================================================================
+// MARGIN HEALTH BATCH (margin_health)
+// =============================================================================
+
+/// Prove: liquidatable matches the maintenance check, and for open positions
+/// without mark overflow it is exactly equity <= maintenance_required.
+#[kani::proof]
+fn kani_margin_health_liquidatable_matches_maintenance() {
+    let capital: u128 = kani::any();
+    let pnl: i128 = kani::any();
+    let pos: i128 = kani::any();
+    let entry: u64 = kani::any();
+    let price: u64 = kani::any();
+    let im_bps: u64 = kani::any();
+    let mm_bps: u64 = kani::any();
+    kani::assume(capital <= KANI_MAX_QUOTIENT as u128);
+    kani::assume(pnl > -(KANI_MAX_QUOTIENT as i128) && pnl < KANI_MAX_QUOTIENT as i128);
+    kani::assume(pos > -(KANI_MAX_QUOTIENT as i128) && pos < KANI_MAX_QUOTIENT as i128);
+    kani::assume(entry <= KANI_MAX_QUOTIENT && price <= KANI_MAX_QUOTIENT);
+    kani::assume(mm_bps <= 10_000 && im_bps <= 10_000);
+
+    let h = margin_health(capital, pnl, pos, entry, price, im_bps, mm_bps);
+    assert_eq!(
+        h.liquidatable,
+        below_margin_mtm(capital, pnl, pos, entry, price, mm_bps)
+    );
+    if pos != 0 {
+        assert_eq!(h.liquidatable, h.equity <= h.maintenance_required);
+    } else {
+        assert!(!h.liquidatable);
+    }
+}
+
+// =============================================================================
+// SYSTEM LEVERAGE CAP (system_leverage_ok)
+// =============================================================================
+
+/// Prove: An accepted OI-increasing trade leaves OI notional within
+/// max_x * total_capital; OI reductions and a disabled cap always pass.
+#[kani::proof]
+fn kani_system_leverage_ok_bounds_notional() {
+    let oi_before: u128 = kani::any();
+    let oi_after: u128 = kani::any();
+    let price: u64 = kani::any();
+    let capital: u128 = kani::any();
+    let max_x: u64 = kani::any();
+    kani::assume(oi_after <= u64::MAX as u128);
+    kani::assume(capital <= u64::MAX as u128);
+
+    let ok = system_leverage_ok(oi_before, oi_after, price, capital, max_x);
+    if oi_after <= oi_before || max_x == 0 {
+        assert!(ok);
+    } else if ok {
+        assert!(oi_after * price as u128 / 1_000_000 <= capital * max_x as u128);
+    }
+}
+
+// =============================================================================
+// CRANK DEAD-MAN'S SWITCH (crank_deadman_blocks)
+// =============================================================================
+
+/// Prove: Beyond the deadman threshold every OI-increasing trade is blocked,
+/// reductions never are, and a crank at the current slot re-enables trading.
+#[kani::proof]
+fn kani_crank_deadman_blocks_stale_openings_only() {
+    let now: u64 = kani::any();
+    let last_crank: u64 = kani::any();
+    let deadman: u64 = kani::any();
+    let oi_before: u128 = kani::any();
+    let oi_after: u128 = kani::any();
+    kani::assume(deadman != 0);
+
+    if oi_after > oi_before && now.saturating_sub(last_crank) > deadman {
+        assert!(crank_deadman_blocks(
+            now, last_crank, deadman, oi_before, oi_after
+        ));
+    }
+    if oi_after <= oi_before {
+        assert!(!crank_deadman_blocks(
+            now, last_crank, deadman, oi_before, oi_after
+        ));
+    }
+    // Fresh crank (last_crank_slot = now) lifts the block
+    assert!(!crank_deadman_blocks(
+        now, now, deadman, oi_before, oi_after
+    ));
+}
+
+// =============================================================================
+// CASCADE STRESS (account_deficit)
+// =============================================================================
+
+/// Prove: An account with a deficit is below maintenance for any margin
+/// setting (it is liquidated in the simulation), and a flat account with
+/// non-negative capital + pnl has no deficit.
+#[kani::proof]
+fn kani_account_deficit_implies_liquidatable() {
+    let capital: u128 = kani::any();
+    let pnl: i128 = kani::any();
+    let pos: i128 = kani::any();
+    let entry: u64 = kani::any();
+    let price: u64 = kani::any();
+    let mm_bps: u64 = kani::any();
+    kani::assume(capital <= KANI_MAX_QUOTIENT as u128);
+    kani::assume(pnl > -(KANI_MAX_QUOTIENT as i128) && pnl < KANI_MAX_QUOTIENT as i128);
+    kani::assume(pos > -(KANI_MAX_QUOTIENT as i128) && pos < KANI_MAX_QUOTIENT as i128);
+    kani::assume(entry <= KANI_MAX_QUOTIENT && price <= KANI_MAX_QUOTIENT);
+
+    let d = account_deficit(capital, pnl, pos, entry, price);
+    if d > 0 && pos != 0 {
+        assert!(below_margin_mtm(capital, pnl, pos, entry, price, mm_bps));
+    }
+    if pos == 0 && capital as i128 + pnl >= 0 {
+        assert_eq!(d, 0);
+    }
+}
+
+// =============================================================================
+// MINIMUM DEPOSIT FLOOR (min_deposit_blocks)
+// =============================================================================
+
+/// Prove: A below-minimum deposit is rejected for a healthy account but
+/// accepted when it lifts an undercollateralized account above maintenance;
+/// deposits at or above the minimum (or with the floor disabled) always pass.
+#[kani::proof]
+fn kani_min_deposit_blocks_except_rescue() {
+    let amount: u64 = kani::any();
+    let min: u64 = kani::any();
+    let below_after: bool = kani::any();
+
+    if min != 0 && amount < min {
+        assert!(min_deposit_blocks(amount, min, false, false, below_after));
+        assert!(!min_deposit_blocks(amount, min, false, true, false));
+    }
+    if min == 0 || amount >= min {
+        assert!(!min_deposit_blocks(
+            amount,
+            min,
+            kani::any(),
+            kani::any(),
+            below_after
+        ));
+    }
+}
+
+/// Prove: An LP deposit is never blocked by the user-only floor, while a
+/// user deposit of the same size on a healthy account is.
+#[kani::proof]
+fn kani_min_deposit_exempts_lp() {
+    let amount: u64 = kani::any();
+    let min: u64 = kani::any();
+    let below_before: bool = kani::any();
+    let below_after: bool = kani::any();
+
+    assert!(!min_deposit_blocks(
+        amount,
+        min,
+        true,
+        below_before,
+        below_after
+    ));
+    if min != 0 && amount < min {
+        assert!(min_deposit_blocks(amount, min, false, false, below_after));
+    }
+}
+
+// =============================================================================
+// TRADE FEE ESTIMATE (trade_fee)
+// =============================================================================
+
+/// Prove: trade_fee is exactly ceil(notional * fee_bps / 10_000).
+#[kani::proof]
+fn kani_trade_fee_is_ceil_of_notional_bps() {
+    let abs_size: u128 = kani::any();
+    let price: u64 = kani::any();
+    let fee_bps: u64 = kani::any();
+    kani::assume(abs_size <= KANI_MAX_QUOTIENT as u128 * 1_000);
+    kani::assume(price <= 1_000_000_000);
+    kani::assume(fee_bps <= 10_000);
+
+    let notional = abs_size * price as u128 / 1_000_000;
+    let fee = trade_fee(abs_size, price, fee_bps);
+    assert!(fee * 10_000 >= notional * fee_bps as u128);
+    if fee > 0 {
+        assert!((fee - 1) * 10_000 < notional * fee_bps as u128);
+    }
+}
+
+// =============================================================================
+// WIND-DOWN PAYOUTS (close_at_mark, wind_down_payout)
+// =============================================================================
+
+/// Prove: For two accounts, wind-down payouts sum to at most the vault, and
+/// no profit is paid unless every account's capital is paid in full.
+#[kani::proof]
+fn kani_wind_down_payouts_bounded_by_vault() {
+    let cap_a: u128 = kani::any();
+    let cap_b: u128 = kani::any();
+    let prof_a: u128 = kani::any();
+    let prof_b: u128 = kani::any();
+    let vault: u128 = kani::any();
+    let insurance: u128 = kani::any();
+    kani::assume(cap_a <= KANI_MAX_QUOTIENT as u128 && cap_b <= KANI_MAX_QUOTIENT as u128);
+    kani::assume(prof_a <= KANI_MAX_QUOTIENT as u128 && prof_b <= KANI_MAX_QUOTIENT as u128);
+    kani::assume(vault <= 4 * KANI_MAX_QUOTIENT as u128);
+    kani::assume(insurance <= KANI_MAX_QUOTIENT as u128);
+    let c_tot = cap_a + cap_b;
+    let p_tot = prof_a + prof_b;
+
+    let pay_a = wind_down_payout(cap_a, prof_a, c_tot, p_tot, vault, insurance);
+    let pay_b = wind_down_payout(cap_b, prof_b, c_tot, p_tot, vault, insurance);
+    assert!(pay_a + pay_b <= vault);
+    if pay_a > cap_a || pay_b > cap_b {
+        assert!(vault >= c_tot, "profit only after capital is fully covered");
+    }
+}
+
+/// Prove: Closing at the mark never creates capital and moves gains to profit.
+#[kani::proof]
+fn kani_close_at_mark_capital_first() {
+    let capital: u128 = kani::any();
+    let pnl: i128 = kani::any();
+    let mark: i128 = kani::any();
+
+    let (cap_after, profit) = close_at_mark(capital, pnl, mark);
+    assert!(cap_after <= capital);
+    assert!(profit == 0 || cap_after == capital);
+}
+
+// =============================================================================
+// PER-OWNER ACCOUNT CAP (owner_account_limit_reached)
+// =============================================================================
+
+/// Prove: A zero cap disables the limit for any number of owned accounts.
+#[kani::proof]
+fn kani_owner_account_limit_zero_cap_disables() {
+    let owned: u64 = kani::any();
+    assert!(!owner_account_limit_reached(owned, 0));
+}
+
+/// Prove: Under a nonzero cap the limit is monotone in the owned count with
+/// its boundary at the cap: once refused, owning more stays refused, and one
+/// below the cap (e.g. after a close) is admitted.
+#[kani::proof]
+fn kani_owner_account_limit_monotone_in_owned() {
+    let cap: u64 = kani::any();
+    let owned: u64 = kani::any();
+    let more: u64 = kani::any();
+    kani::assume(cap != 0);
+    kani::assume(more >= owned);
+
+    if owner_account_limit_reached(owned, cap) {
+        assert!(owner_account_limit_reached(more, cap));
+    }
+    assert_eq!(owner_account_limit_reached(owned, cap), owned >= cap);
+    assert!(!owner_account_limit_reached(cap - 1, cap));
+}
+
+/// Prove: Raising a nonzero cap never refuses an owner it admitted before.
+#[kani::proof]
+fn kani_owner_account_limit_raising_cap_never_blocks() {
+    let cap: u64 = kani::any();
+    let higher: u64 = kani::any();
+    let owned: u64 = kani::any();
+    kani::assume(cap != 0);
+    kani::assume(higher >= cap);
+
+    if !owner_account_limit_reached(owned, cap) {
+        assert!(!owner_account_limit_reached(owned, higher));
+    }
+}
+
+// =============================================================================
+// WARMUP COUNTDOWN (slots_to_full_warmup)
+// =============================================================================
+
+/// Prove: After slots_to_full_warmup more slots the PnL is fully warmed, and
+/// one slot earlier it is not (when a countdown is running).
+#[kani::proof]
+fn kani_slots_to_full_warmup_exact() {
+    let avail: u128 = kani::any();
+    let slope: u128 = kani::any();
+    let elapsed: u64 = kani::any();
+    kani::assume(avail <= KANI_MAX_QUOTIENT as u128);
+    kani::assume(slope >= 1 && slope <= KANI_MAX_QUOTIENT as u128);
+    kani::assume(elapsed <= KANI_MAX_QUOTIENT);
+
+    let rem = slots_to_full_warmup(avail, slope, elapsed);
+    assert_eq!(warmed_pnl(avail, slope, elapsed + rem), avail);
+    if rem > 0 {
+        assert!(warmed_pnl(avail, slope, elapsed + rem - 1) < avail);
+    }
+}
+
+/// Prove: Zero slope with PnL pending never finishes; nothing pending is 0.
+#[kani::proof]
+fn kani_slots_to_full_warmup_edges() {
+    let avail: u128 = kani::any();
+    let slope: u128 = kani::any();
+    let elapsed: u64 = kani::any();
+
+    assert_eq!(slots_to_full_warmup(0, slope, elapsed), 0);
+    if avail > 0 {
+        assert_eq!(slots_to_full_warmup(avail, 0, elapsed), u64::MAX);
+    }
+}
+
+// =============================================================================
+// OI CONCENTRATION (concentration_bps)
+// =============================================================================
+
+/// Prove: One account holding all OI reports 10_000 bps; an empty book
+/// reports 0; the result never exceeds 10_000.
+#[kani::proof]
+fn kani_concentration_bps_bounds() {
+    let max_abs: u128 = kani::any();
+    let total: u128 = kani::any();
+    kani::assume(total > 0 && total <= u64::MAX as u128);
+
+    assert_eq!(concentration_bps(total, total), 10_000);
+    assert_eq!(concentration_bps(max_abs, 0), 0);
+    assert!(concentration_bps(max_abs, total) <= 10_000);
+}
+
+// =============================================================================
+// CRANK INTERVAL (crank_interval)
+// =============================================================================
+
+/// Prove: The interval never exceeds max staleness and never grows as
+/// volatility increases.
+#[kani::proof]
+fn kani_crank_interval_monotone_bounded() {
+    let v1: u16 = kani::any();
+    let v2: u16 = kani::any();
+    let buffer: u16 = kani::any();
+    let max_staleness: u64 = kani::any();
+    kani::assume(v1 <= v2);
+
+    let i1 = crank_interval(v1, buffer, max_staleness);
+    let i2 = crank_interval(v2, buffer, max_staleness);
+    assert!(i1 <= max_staleness && i2 <= max_staleness);
+    assert!(i2 <= i1);
+}
+
+// =============================================================================
+// LIMITED LIQUIDATION (liquidation_limit_ok)
+// =============================================================================
+
+/// Prove: With a limit, a long closes only at or above it and a short only
+/// at or below it; without a limit every price passes (plain liquidation).
+#[kani::proof]
+fn kani_liquidation_limit_side_aware() {
+    let pos: i128 = kani::any();
+    let price: u64 = kani::any();
+    let limit: u64 = kani::any();
+
+    assert!(liquidation_limit_ok(pos, price, None));
+    let ok = liquidation_limit_ok(pos, price, Some(limit));
+    if pos > 0 {
+        assert_eq!(ok, price >= limit);
+    } else if pos < 0 {
+        assert_eq!(ok, price <= limit);
+    } else {
+        assert!(ok);
+    }
+}
+
+// =============================================================================
+// BUYING POWER (free_equity + max_position_for_collateral)
+// =============================================================================
+
+/// Prove: Opening the size that buying power allows (paying its fee) keeps
+/// equity strictly above the combined initial margin of the existing and new
+/// notional, and one unit more does not.
+#[kani::proof]
+#[kani::unwind(10)]
+fn kani_buying_power_at_margin_limit() {
+    let equity: u128 = kani::any();
+    let init_req: u128 = kani::any();
+    let price: u64 = kani::any();
+    let im_bps: u64 = kani::any();
+    let fee_bps: u64 = kani::any();
+    kani::assume(equity <= 1_000 && init_req <= 1_000);
+    kani::assume(price >= 1 && price <= 1_000_000);
+    kani::assume(im_bps <= 10_000);
+    kani::assume(fee_bps <= 1_000);
+    let max_abs: u128 = 256;
+
+    let free = free_equity(equity, init_req);
+    let s = max_position_for_collateral(free, price, im_bps, fee_bps, max_abs);
+    let fits = |size: u128| {
+        let fee = trade_fee(size, price, fee_bps);
+        let notional = size * price as u128 / 1_000_000;
+        fee < free && equity - fee > init_req + margin_required(notional, im_bps)
+    };
+    if s > 0 {
+        assert!(fits(s));
+    }
+    if s < max_abs {
+        assert!(!fits(s + 1));
+    }
+}
+
+// =============================================================================
+// COST OF CARRY (carry_per_slot)
+// =============================================================================
+
+/// Prove: A flat position's carry is exactly the maintenance fee (the
+/// funding component is zero at any rate and price).
+#[kani::proof]
+fn kani_carry_per_slot_flat_is_fee_only() {
+    let fee: u64 = kani::any();
+    let price: u64 = kani::any();
+    let rate: i64 = kani::any();
+
+    assert_eq!(carry_per_slot(fee as u128, 0, price, rate), fee as i128);
+}
+
+// =============================================================================
+// MARGIN CHANGE IMPACT (newly_liquidatable)
+// =============================================================================
+
+/// Prove: An account is counted iff it is above the current maintenance
+/// requirement and at or below the proposed one; loosening the requirement
+/// never counts anyone.
+#[kani::proof]
+fn kani_newly_liquidatable_exact() {
+    let capital: u128 = kani::any();
+    let pnl: i128 = kani::any();
+    let pos: i128 = kani::any();
+    let entry: u64 = kani::any();
+    let price: u64 = kani::any();
+    let old_bps: u64 = kani::any();
+    let new_bps: u64 = kani::any();
+    kani::assume(capital <= KANI_MAX_QUOTIENT as u128);
+    kani::assume(pnl > -(KANI_MAX_QUOTIENT as i128) && pnl < KANI_MAX_QUOTIENT as i128);
+    kani::assume(pos > -(KANI_MAX_QUOTIENT as i128) && pos < KANI_MAX_QUOTIENT as i128);
+    kani::assume(entry <= KANI_MAX_QUOTIENT && price <= KANI_MAX_QUOTIENT);
+    kani::assume(old_bps <= 10_000 && new_bps <= 10_000);
+
+    let n = newly_liquidatable(capital, pnl, pos, entry, price, old_bps, new_bps);
+    let below_old = below_margin_mtm(capital, pnl, pos, entry, price, old_bps);
+    let below_new = below_margin_mtm(capital, pnl, pos, entry, price, new_bps);
+    assert_eq!(n, below_new && !below_old);
+    if new_bps <= old_bps {
+        assert!(!n);
+    }
+}
+
+// =============================================================================
+// UNDERWATER ACCOUNTS (account_deficit)
+// =============================================================================
+
+/// Prove: A listed account (deficit > 0) has clamped MTM equity of zero and
+/// its deficit is exactly loss - capital, where loss = -(pnl + mark_pnl).
+#[kani::proof]
+fn kani_underwater_deficit_is_loss_minus_capital() {
+    let capital: u128 = kani::any();
+    let pnl: i128 = kani::any();
+    let pos: i128 = kani::any();
+    let entry: u64 = kani::any();
+    let price: u64 = kani::any();
+    kani::assume(capital <= KANI_MAX_QUOTIENT as u128);
+    kani::assume(pnl > -(KANI_MAX_QUOTIENT as i128) && pnl < KANI_MAX_QUOTIENT as i128);
+    kani::assume(pos > -(KANI_MAX_QUOTIENT as i128) && pos < KANI_MAX_QUOTIENT as i128);
+    kani::assume(pos != 0);
+    kani::assume(entry <= KANI_MAX_QUOTIENT && price <= KANI_MAX_QUOTIENT);
+
+    let d = account_deficit(capital, pnl, pos, entry, price);
+    if d > 0 {
+        let mark = mark_pnl(pos, entry, price).unwrap();
+        assert_eq!(equity_mtm(capital, pnl, mark), 0);
+        let loss = -(pnl + mark);
+        assert_eq!(d as i128, loss - capital as i128);
+    }
+}
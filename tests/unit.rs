@@ -3,9 +3,9 @@
 //! These tests verify the Solana program wrapper's instruction handling,
 //! including account validation, state management, and invariants.
 
-use percolator::{I128, MAX_ACCOUNTS, U128};
+use percolator::{AccountKind, I128, MAX_ACCOUNTS, U128};
 use percolator_prog::{
-    constants::{MAGIC, VERSION},
+    constants::{ENGINE_OFF, MAGIC, VERSION},
     error::PercolatorError,
     oracle,
     processor::process_instruction,
@@ -359,12 +359,105 @@ fn encode_close_slab() -> Vec<u8> {
     vec![13u8]
 }
 
+fn encode_close_account(user_idx: u16) -> Vec<u8> {
+    let mut data = vec![8u8];
+    encode_u16(user_idx, &mut data);
+    data
+}
+
 fn encode_topup_insurance(amount: u64) -> Vec<u8> {
     let mut data = vec![9u8];
     encode_u64(amount, &mut data);
     data
 }
 
+fn encode_set_min_insurance_ratio(min_bps: u64) -> Vec<u8> {
+    let mut data = vec![22u8];
+    encode_u64(min_bps, &mut data);
+    data
+}
+
+fn encode_sweep_orphaned_vault() -> Vec<u8> {
+    vec![23u8]
+}
+
+fn encode_set_margin_params(initial_bps: u64, maintenance_bps: u64) -> Vec<u8> {
+    let mut data = vec![24u8];
+    encode_u64(initial_bps, &mut data);
+    encode_u64(maintenance_bps, &mut data);
+    data
+}
+
+fn encode_set_strict_withdraw(enabled: u8) -> Vec<u8> {
+    vec![25u8, enabled]
+}
+
+fn encode_set_maintenance_fee(new_fee: u128) -> Vec<u8> {
+    let mut data = vec![15u8];
+    encode_u128(new_fee, &mut data);
+    data
+}
+
+/// UpdateConfig that keeps the current config except the funding inventory scale.
+fn encode_update_funding_scale(slab: &[u8], funding_inv_scale_notional_e6: u128) -> Vec<u8> {
+    let c = state::read_config(slab);
+    let mut data = vec![14u8];
+    encode_u64(c.funding_horizon_slots, &mut data);
+    encode_u64(c.funding_k_bps, &mut data);
+    encode_u128(funding_inv_scale_notional_e6, &mut data);
+    data.extend_from_slice(&c.funding_max_premium_bps.to_le_bytes());
+    data.extend_from_slice(&c.funding_max_bps_per_slot.to_le_bytes());
+    encode_u128(c.thresh_floor, &mut data);
+    encode_u64(c.thresh_risk_bps, &mut data);
+    encode_u64(c.thresh_update_interval_slots, &mut data);
+    encode_u64(c.thresh_step_bps, &mut data);
+    encode_u64(c.thresh_alpha_bps, &mut data);
+    encode_u128(c.thresh_min, &mut data);
+    encode_u128(c.thresh_max, &mut data);
+    encode_u128(c.thresh_min_step, &mut data);
+    data
+}
+
+fn encode_set_funding_paused(paused: u8) -> Vec<u8> {
+    vec![26u8, paused]
+}
+
+fn encode_contribute_insurance(user_idx: u16, amount: u64) -> Vec<u8> {
+    let mut data = vec![27u8];
+    encode_u16(user_idx, &mut data);
+    encode_u64(amount, &mut data);
+    data
+}
+
+fn encode_set_max_system_leverage(max_leverage_x: u64) -> Vec<u8> {
+    let mut data = vec![28u8];
+    encode_u64(max_leverage_x, &mut data);
+    data
+}
+
+fn encode_set_min_deposit(min_deposit: u64) -> Vec<u8> {
+    let mut data = vec![30u8];
+    encode_u64(min_deposit, &mut data);
+    data
+}
+
+fn encode_set_max_accounts_per_owner(max_accounts: u64) -> Vec<u8> {
+    let mut data = vec![31u8];
+    encode_u64(max_accounts, &mut data);
+    data
+}
+
+fn encode_liquidate_limited(target_idx: u16, limit_price_e6: u64) -> Vec<u8> {
+    let mut data = vec![32u8];
+    encode_u16(target_idx, &mut data);
+    encode_u64(limit_price_e6, &mut data);
+    data
+}
+
+fn encode_migrate_slab() -> Vec<u8> {
+    vec![33u8]
+}
+
 fn find_idx_by_owner(data: &[u8], owner: Pubkey) -> Option<u16> {
     let engine = zc::engine_ref(data).ok()?;
     for i in 0..MAX_ACCOUNTS {
@@ -375,6 +468,142 @@ fn find_idx_by_owner(data: &[u8], owner: Pubkey) -> Option<u16> {
     None
 }
 
+/// Market with one user and one LP, each with 1000 deposited, after a
+/// 100-unit trade (user long, LP short) at the fixture oracle.
+struct TradedMarket {
+    f: MarketFixture,
+    user: TestAccount,
+    lp: TestAccount,
+    user_idx: u16,
+    lp_idx: u16,
+}
+
+fn setup_traded_market() -> TradedMarket {
+    let mut f = setup_market();
+    let init_data = encode_init_market(&f, 100);
+    {
+        let mut dummy_ata = TestAccount::new(Pubkey::new_unique(), Pubkey::default(), 0, vec![]);
+        let init_accounts = vec![
+            f.admin.to_info(),
+            f.slab.to_info(),
+            f.mint.to_info(),
+            f.vault.to_info(),
+            f.token_prog.to_info(),
+            f.clock.to_info(),
+            f.rent.to_info(),
+            dummy_ata.to_info(),
+            f.system.to_info(),
+        ];
+        process_instruction(&f.program_id, &init_accounts, &init_data).unwrap();
+    }
+
+    let mut user = TestAccount::new(
+        Pubkey::new_unique(),
+        solana_program::system_program::id(),
+        0,
+        vec![],
+    )
+    .signer();
+    let mut user_ata = TestAccount::new(
+        Pubkey::new_unique(),
+        spl_token::ID,
+        0,
+        make_token_account(f.mint.key, user.key, 1000),
+    )
+    .writable();
+    {
+        let accounts = vec![
+            user.to_info(),
+            f.slab.to_info(),
+            user_ata.to_info(),
+            f.vault.to_info(),
+            f.token_prog.to_info(),
+        ];
+        process_instruction(&f.program_id, &accounts, &encode_init_user(0)).unwrap();
+    }
+    let user_idx = find_idx_by_owner(&f.slab.data, user.key).unwrap();
+    {
+        let accounts = vec![
+            user.to_info(),
+            f.slab.to_info(),
+            user_ata.to_info(),
+            f.vault.to_info(),
+            f.token_prog.to_info(),
+            f.clock.to_info(),
+        ];
+        process_instruction(&f.program_id, &accounts, &encode_deposit(user_idx, 1000)).unwrap();
+    }
+
+    let mut lp = TestAccount::new(
+        Pubkey::new_unique(),
+        solana_program::system_program::id(),
+        0,
+        vec![],
+    )
+    .signer();
+    let mut lp_ata = TestAccount::new(
+        Pubkey::new_unique(),
+        spl_token::ID,
+        0,
+        make_token_account(f.mint.key, lp.key, 1000),
+    )
+    .writable();
+    let d1 = TestAccount::new(Pubkey::new_unique(), Pubkey::default(), 0, vec![]);
+    let d2 = TestAccount::new(Pubkey::new_unique(), Pubkey::default(), 0, vec![]);
+    {
+        let matcher_prog_key = d1.key;
+        let matcher_ctx_key = d2.key;
+        let accs = vec![
+            lp.to_info(),
+            f.slab.to_info(),
+            lp_ata.to_info(),
+            f.vault.to_info(),
+            f.token_prog.to_info(),
+        ];
+        process_instruction(
+            &f.program_id,
+            &accs,
+            &encode_init_lp(matcher_prog_key, matcher_ctx_key, 0),
+        )
+        .unwrap();
+    }
+    let lp_idx = find_idx_by_owner(&f.slab.data, lp.key).unwrap();
+    {
+        let accounts = vec![
+            lp.to_info(),
+            f.slab.to_info(),
+            lp_ata.to_info(),
+            f.vault.to_info(),
+            f.token_prog.to_info(),
+            f.clock.to_info(),
+        ];
+        process_instruction(&f.program_id, &accounts, &encode_deposit(lp_idx, 1000)).unwrap();
+    }
+
+    {
+        let accounts = vec![
+            user.to_info(),
+            lp.to_info(),
+            f.slab.to_info(),
+            f.clock.to_info(),
+            f.pyth_index.to_info(),
+        ];
+        process_instruction(
+            &f.program_id,
+            &accounts,
+            &encode_trade(lp_idx, user_idx, 100),
+        )
+        .unwrap();
+    }
+    TradedMarket {
+        f,
+        user,
+        lp,
+        user_idx,
+        lp_idx,
+    }
+}
+
 // --- Tests ---
 
 #[test]
@@ -456,6 +685,82 @@ fn test_init_market() {
     assert_eq!(engine.params.max_accounts, MAX_ACCOUNTS as u64);
 }
 
+#[test]
+fn test_migrate_v1_slab_layout() {
+    use percolator_prog::constants::{
+        ENGINE_OFF, HEADER_LEN, V1_CONFIG_LEN, V1_ENGINE_OFF, V1_VERSION,
+    };
+
+    let mut f = setup_market();
+    let data = encode_init_market(&f, 100);
+    {
+        let mut dummy_ata = TestAccount::new(Pubkey::new_unique(), Pubkey::default(), 0, vec![]);
+        let accounts = vec![
+            f.admin.to_info(),
+            f.slab.to_info(),
+            f.mint.to_info(),
+            f.vault.to_info(),
+            f.token_prog.to_info(),
+            f.clock.to_info(),
+            f.rent.to_info(),
+            dummy_ata.to_info(),
+            f.system.to_info(),
+        ];
+        process_instruction(&f.program_id, &accounts, &data).unwrap();
+    }
+    let current = f.slab.data.clone();
+
+    // Current-layout slabs are not migrated again
+    {
+        let accs = vec![f.slab.to_info()];
+        let res = process_instruction(&f.program_id, &accs, &encode_migrate_slab());
+        assert_eq!(res, Err(PercolatorError::InvalidSlabLen.into()));
+    }
+
+    // Pre-policy (version 1) image of the same market: config without the
+    // policy block, engine at the old offset
+    let mut v1 = current[..HEADER_LEN + V1_CONFIG_LEN].to_vec();
+    v1.resize(V1_ENGINE_OFF, 0);
+    v1.extend_from_slice(&current[ENGINE_OFF..]);
+    let mut h = state::read_header(&v1);
+    h.version = V1_VERSION;
+    state::write_header(&mut v1, &h);
+
+    // Every other instruction rejects a version-1 slab until it is migrated
+    f.slab.data = v1.clone();
+    {
+        let accs = vec![f.admin.to_info(), f.slab.to_info()];
+        let res = process_instruction(&f.program_id, &accs, &encode_set_max_accounts_per_owner(3));
+        assert_eq!(res, Err(PercolatorError::InvalidSlabLen.into()));
+    }
+
+    // A v1-length slab that does not carry version 1 is refused before growing
+    let mut wrong = v1.clone();
+    state::write_header(&mut wrong, &state::read_header(&current));
+    f.slab.data = wrong;
+    {
+        let accs = vec![f.slab.to_info()];
+        let res = process_instruction(&f.program_id, &accs, &encode_migrate_slab());
+        assert_eq!(res, Err(PercolatorError::InvalidVersion.into()));
+    }
+
+    // The grow itself needs the runtime, so apply the layout rewrite directly
+    let v1_len = v1.len();
+    let mut grown = v1;
+    grown.resize(v1_len + ENGINE_OFF - V1_ENGINE_OFF, 0);
+    state::migrate_v1_layout(&mut grown, v1_len);
+    assert_eq!(grown, current);
+
+    // The migrated slab is accepted and its policy knobs start disabled
+    f.slab.data = grown;
+    assert_eq!(state::read_config(&f.slab.data).max_accounts_per_owner, 0);
+    {
+        let accs = vec![f.admin.to_info(), f.slab.to_info()];
+        process_instruction(&f.program_id, &accs, &encode_set_max_accounts_per_owner(3)).unwrap();
+    }
+    assert_eq!(state::read_config(&f.slab.data).max_accounts_per_owner, 3);
+}
+
 #[test]
 #[cfg(feature = "test")]
 fn test_init_user() {
@@ -740,62 +1045,456 @@ fn test_trade() {
 }
 
 #[test]
-#[cfg(feature = "test")]
-fn test_withdraw_wrong_signer() {
-    let mut f = setup_market();
-    let init_data = encode_init_market(&f, 0);
-    {
-        let mut dummy = TestAccount::new(Pubkey::new_unique(), Pubkey::default(), 0, vec![]);
-        let accs = vec![
-            f.admin.to_info(),
-            f.slab.to_info(),
-            f.mint.to_info(),
-            f.vault.to_info(),
-            f.token_prog.to_info(),
-            f.clock.to_info(),
-            f.rent.to_info(),
-            dummy.to_info(),
-            f.system.to_info(),
-        ];
-        process_instruction(&f.program_id, &accs, &init_data).unwrap();
-    }
+fn test_lp_inventory_mirrors_user_position() {
+    let t = setup_traded_market();
+    let engine = zc::engine_ref(&t.f.slab.data).unwrap();
+
+    // LP inventory mirrors the user's position (zero-sum book)
+    assert_eq!(percolator_prog::lp_inventory(engine, t.lp_idx), Some(-100));
+    assert_eq!(percolator_prog::lp_inventory(engine, t.user_idx), None);
+    let mut out = [(0u16, 0i128); 4];
+    let n = percolator_prog::all_lp_inventory(engine, &mut out);
+    assert_eq!(&out[..n], &[(t.lp_idx, -100)]);
+    let user_pos = engine.accounts[t.user_idx as usize].position_size.get();
+    assert_eq!(user_pos + out[..n].iter().map(|e| e.1).sum::<i128>(), 0);
+}
 
-    let mut user = TestAccount::new(
-        Pubkey::new_unique(),
-        solana_program::system_program::id(),
-        0,
-        vec![],
-    )
-    .signer();
-    let mut user_ata = TestAccount::new(
-        Pubkey::new_unique(),
-        spl_token::ID,
-        0,
-        make_token_account(f.mint.key, user.key, 1000),
-    )
-    .writable();
+#[test]
+fn test_trade_i128_min_size_rejected() {
+    let mut t = setup_traded_market();
+
+    // i128::MIN size is rejected with Overflow and leaves the slab untouched
+    let slab_before = t.f.slab.data.clone();
     {
         let accounts = vec![
-            user.to_info(),
-            f.slab.to_info(),
-            user_ata.to_info(),
-            f.vault.to_info(),
-            f.token_prog.to_info(),
+            t.user.to_info(),
+            t.lp.to_info(),
+            t.f.slab.to_info(),
+            t.f.clock.to_info(),
+            t.f.pyth_index.to_info(),
         ];
-        process_instruction(&f.program_id, &accounts, &encode_init_user(0)).unwrap();
+        let res = process_instruction(
+            &t.f.program_id,
+            &accounts,
+            &encode_trade(t.lp_idx, t.user_idx, i128::MIN),
+        );
+        assert_eq!(res, Err(PercolatorError::EngineOverflow.into()));
     }
-    let user_idx = find_idx_by_owner(&f.slab.data, user.key).unwrap();
+    assert_eq!(t.f.slab.data, slab_before);
+}
+
+#[test]
+fn test_system_leverage_cap_blocks_oi_growth() {
+    let mut t = setup_traded_market();
 
+    // OI notional already exceeds 1x capital, so a trade that grows OI is
+    // rejected
+    {
+        let accs = vec![t.f.admin.to_info(), t.f.slab.to_info()];
+        process_instruction(&t.f.program_id, &accs, &encode_set_max_system_leverage(1)).unwrap();
+    }
     {
         let accounts = vec![
-            user.to_info(),
-            f.slab.to_info(),
-            user_ata.to_info(),
-            f.vault.to_info(),
-            f.token_prog.to_info(),
-            f.clock.to_info(),
+            t.user.to_info(),
+            t.lp.to_info(),
+            t.f.slab.to_info(),
+            t.f.clock.to_info(),
+            t.f.pyth_index.to_info(),
         ];
-        process_instruction(&f.program_id, &accounts, &encode_deposit(user_idx, 500)).unwrap();
+        let res = process_instruction(
+            &t.f.program_id,
+            &accounts,
+            &encode_trade(t.lp_idx, t.user_idx, 100),
+        );
+        assert_eq!(res, Err(PercolatorError::SystemLeverageExceeded.into()));
+    }
+}
+
+#[test]
+fn test_simulate_cascade_balanced_book() {
+    let t = setup_traded_market();
+    let engine = zc::engine_ref(&t.f.slab.data).unwrap();
+
+    // Balanced, well-capitalized book: no cascade at the current oracle
+    let report = percolator_prog::simulate_cascade(engine, 100_000_000);
+    assert_eq!(report.accounts_liquidated, 0);
+    assert_eq!(report.insurance_consumed, 0);
+    assert_eq!(report.socialized_loss, 0);
+
+    // Halving the price wipes out the 10x long: exactly one liquidation
+    let report = percolator_prog::simulate_cascade(engine, 50_000_000);
+    assert_eq!(report.accounts_liquidated, 1);
+}
+
+#[test]
+fn test_accounts_by_kind() {
+    let t = setup_traded_market();
+    let engine = zc::engine_ref(&t.f.slab.data).unwrap();
+
+    let mut users = [0u16; 4];
+    let mut lps = [0u16; 4];
+    let nu = percolator_prog::accounts_by_kind(engine, AccountKind::User, &mut users);
+    let nl = percolator_prog::accounts_by_kind(engine, AccountKind::LP, &mut lps);
+    assert_eq!(&users[..nu], &[t.user_idx]);
+    assert_eq!(&lps[..nl], &[t.lp_idx]);
+
+    // A full buffer stops the scan
+    let mut none: [u16; 0] = [];
+    assert_eq!(
+        percolator_prog::accounts_by_kind(engine, AccountKind::User, &mut none),
+        0
+    );
+}
+
+#[test]
+fn test_positions_zero_sum_after_trade() {
+    let t = setup_traded_market();
+    let engine = zc::engine_ref(&t.f.slab.data).unwrap();
+
+    // Every fill applies +size to the user and -size to the LP
+    assert_eq!(percolator_prog::position_sum(engine), Some(0));
+    assert!(percolator_prog::positions_zero_sum(engine));
+}
+
+#[test]
+fn test_average_entry_is_entry_price() {
+    let t = setup_traded_market();
+    let engine = zc::engine_ref(&t.f.slab.data).unwrap();
+
+    for idx in [t.user_idx, t.lp_idx] {
+        let acc = &engine.accounts[idx as usize];
+        assert_ne!(acc.position_size.get(), 0);
+        assert_eq!(
+            percolator_prog::average_entry(engine, idx),
+            Some(acc.entry_price)
+        );
+    }
+    let unused = (0..MAX_ACCOUNTS as u16)
+        .find(|&i| !engine.is_used(i as usize))
+        .unwrap();
+    assert_eq!(percolator_prog::average_entry(engine, unused), None);
+}
+
+#[test]
+fn test_aggregate_drift_zero_after_trade() {
+    let t = setup_traded_market();
+    let engine = zc::engine_ref(&t.f.slab.data).unwrap();
+
+    assert_eq!(
+        percolator_prog::aggregate_drift(engine),
+        percolator_prog::AggregateDelta::default()
+    );
+}
+
+#[test]
+fn test_survives_shock() {
+    let t = setup_traded_market();
+    let engine = zc::engine_ref(&t.f.slab.data).unwrap();
+
+    // Both sides are safe at the current oracle
+    assert!(percolator_prog::survives_shock(
+        engine,
+        t.user_idx,
+        100_000_000
+    ));
+    assert!(percolator_prog::survives_shock(
+        engine,
+        t.lp_idx,
+        100_000_000
+    ));
+
+    // Halving the price sinks the 10x long while the short gains
+    assert!(!percolator_prog::survives_shock(
+        engine, t.user_idx, 50_000_000
+    ));
+    assert!(percolator_prog::survives_shock(
+        engine, t.lp_idx, 50_000_000
+    ));
+
+    // Unused slots report false
+    assert!(!percolator_prog::survives_shock(
+        engine,
+        (MAX_ACCOUNTS - 1) as u16,
+        100_000_000
+    ));
+}
+
+#[test]
+fn test_tvl_breakdown_matches_engine() {
+    let t = setup_traded_market();
+    let engine = zc::engine_ref(&t.f.slab.data).unwrap();
+
+    assert_eq!(percolator_prog::tvl(engine), engine.vault.get());
+    let tvl = percolator_prog::tvl_breakdown(engine);
+    assert_eq!(tvl.user_capital + tvl.lp_capital, engine.c_tot.get());
+    assert_eq!(tvl.insurance, engine.insurance_fund.balance.get());
+}
+
+#[test]
+fn test_crank_liquidates_zombie() {
+    let TradedMarket {
+        mut f,
+        user_idx,
+        lp_idx,
+        ..
+    } = setup_traded_market();
+    {
+        let accs = vec![f.admin.to_info(), f.slab.to_info()];
+        process_instruction(&f.program_id, &accs, &encode_set_margin_params(1_000, 500)).unwrap();
+    }
+
+    // $10 drop wipes the long's 1_000 of capital on 100 contracts
+    f.pyth_index.data = make_pyth(&TEST_FEED_ID, 90_000_000, -6, 1, 101);
+    f.clock.data = make_clock(101, 101);
+    {
+        let engine = zc::engine_ref(&f.slab.data).unwrap();
+        assert!(percolator_prog::is_zombie(engine, user_idx, 90_000_000));
+        assert!(!percolator_prog::is_zombie(engine, lp_idx, 90_000_000));
+        let all = MAX_ACCOUNTS as u16;
+        let mut out = [0u16; 4];
+        assert_eq!(
+            percolator_prog::zombie_accounts(engine, 90_000_000, 0, all, &mut out),
+            1
+        );
+        assert_eq!(out[0], user_idx);
+
+        // The scan is bounded: a window that ends before the zombie misses
+        // it, one that wraps around the end of the slab finds it.
+        let past = user_idx + 1;
+        let mut out = [0u16; 4];
+        assert_eq!(
+            percolator_prog::zombie_accounts(engine, 90_000_000, past, all - past, &mut out),
+            0
+        );
+        assert_eq!(
+            percolator_prog::zombie_accounts(engine, 90_000_000, past, all, &mut out),
+            1
+        );
+        assert_eq!(out[0], user_idx);
+    }
+
+    let mut keeper = TestAccount::new(
+        Pubkey::new_unique(),
+        solana_program::system_program::id(),
+        0,
+        vec![],
+    );
+    {
+        let accs = vec![
+            keeper.to_info(),
+            f.slab.to_info(),
+            f.clock.to_info(),
+            f.pyth_index.to_info(),
+        ];
+        process_instruction(&f.program_id, &accs, &encode_crank_permissionless(0)).unwrap();
+    }
+
+    let engine = zc::engine_ref(&f.slab.data).unwrap();
+    assert_eq!(engine.accounts[user_idx as usize].position_size.get(), 0);
+    assert!(!percolator_prog::is_zombie(engine, user_idx, 90_000_000));
+    let mut out = [0u16; 4];
+    let all = MAX_ACCOUNTS as u16;
+    assert_eq!(
+        percolator_prog::zombie_accounts(engine, 90_000_000, 0, all, &mut out),
+        0
+    );
+}
+
+#[test]
+fn test_decode_liquidate_at_oracle_limited() {
+    use percolator_prog::ix::Instruction;
+
+    match Instruction::decode(&encode_liquidate_limited(7, 95_000_000)).unwrap() {
+        Instruction::LiquidateAtOracle {
+            target_idx,
+            limit_price_e6,
+        } => {
+            assert_eq!(target_idx, 7);
+            assert_eq!(limit_price_e6, Some(95_000_000));
+        }
+        other => panic!("unexpected instruction: {:?}", other),
+    }
+    // Tag 7 stays unlimited
+    match Instruction::decode(&[7u8, 7, 0]).unwrap() {
+        Instruction::LiquidateAtOracle { limit_price_e6, .. } => {
+            assert_eq!(limit_price_e6, None)
+        }
+        other => panic!("unexpected instruction: {:?}", other),
+    }
+    // Missing limit is malformed
+    assert!(Instruction::decode(&[32u8, 7, 0]).is_err());
+}
+
+#[test]
+fn test_liquidate_limited_skips_then_executes() {
+    let TradedMarket {
+        mut f, user_idx, ..
+    } = setup_traded_market();
+    {
+        let accs = vec![f.admin.to_info(), f.slab.to_info()];
+        process_instruction(&f.program_id, &accs, &encode_set_margin_params(1_000, 500)).unwrap();
+    }
+
+    // Fresh crank while the book is still healthy at $100
+    f.clock.data = make_clock(101, 101);
+    let mut keeper = TestAccount::new(
+        Pubkey::new_unique(),
+        solana_program::system_program::id(),
+        0,
+        vec![],
+    );
+    {
+        let accs = vec![
+            keeper.to_info(),
+            f.slab.to_info(),
+            f.clock.to_info(),
+            f.pyth_index.to_info(),
+        ];
+        process_instruction(&f.program_id, &accs, &encode_crank_permissionless(0)).unwrap();
+    }
+
+    // At $94 the long has 400 of equity against 470 maintenance
+    f.pyth_index.data = make_pyth(&TEST_FEED_ID, 94_000_000, -6, 1, 101);
+
+    // Limit above the oracle: a long would sell below it, so the close is
+    // skipped and the engine is untouched
+    let engine_before = f.slab.data[ENGINE_OFF..].to_vec();
+    {
+        let accs = vec![
+            keeper.to_info(),
+            f.slab.to_info(),
+            f.clock.to_info(),
+            f.pyth_index.to_info(),
+        ];
+        process_instruction(
+            &f.program_id,
+            &accs,
+            &encode_liquidate_limited(user_idx, 95_000_000),
+        )
+        .unwrap();
+    }
+    assert!(f.slab.data[ENGINE_OFF..] == engine_before[..]);
+    assert_eq!(
+        state::read_config(&f.slab.data).last_effective_price_e6,
+        94_000_000
+    );
+
+    // Limit within the oracle: behaves like LiquidateAtOracle
+    {
+        let accs = vec![
+            keeper.to_info(),
+            f.slab.to_info(),
+            f.clock.to_info(),
+            f.pyth_index.to_info(),
+        ];
+        process_instruction(
+            &f.program_id,
+            &accs,
+            &encode_liquidate_limited(user_idx, 90_000_000),
+        )
+        .unwrap();
+    }
+    let pos = zc::engine_ref(&f.slab.data).unwrap().accounts[user_idx as usize]
+        .position_size
+        .get();
+    assert!(pos < 100, "liquidation must reduce the long");
+}
+
+#[test]
+fn test_sweep_progress_tracks_crank() {
+    let mut t = setup_traded_market();
+    let before = percolator_prog::sweep_progress(zc::engine_ref(&t.f.slab.data).unwrap());
+    assert_eq!(before.total_used, 2);
+
+    let mut keeper = TestAccount::new(
+        Pubkey::new_unique(),
+        solana_program::system_program::id(),
+        0,
+        vec![],
+    );
+    // Crank one slot at a time until the sweep reports a completed pass
+    let mut completed = None;
+    for slot in 101..=100 + MAX_ACCOUNTS as u64 {
+        t.f.clock.data = make_clock(slot, 100);
+        {
+            let accs = vec![
+                keeper.to_info(),
+                t.f.slab.to_info(),
+                t.f.clock.to_info(),
+                t.f.pyth_index.to_info(),
+            ];
+            process_instruction(&t.f.program_id, &accs, &encode_crank_permissionless(0)).unwrap();
+        }
+        let p = percolator_prog::sweep_progress(zc::engine_ref(&t.f.slab.data).unwrap());
+        assert_eq!(p.total_used, 2);
+        assert!((p.cursor as usize) < MAX_ACCOUNTS);
+        if p.last_completed_slot != before.last_completed_slot {
+            completed = Some((slot, p));
+            break;
+        }
+    }
+    let (slot, p) = completed.expect("crank never completed a sweep");
+    assert_eq!(p.last_completed_slot, slot);
+    assert!(p.started_slot <= slot);
+}
+
+#[test]
+#[cfg(feature = "test")]
+fn test_withdraw_wrong_signer() {
+    let mut f = setup_market();
+    let init_data = encode_init_market(&f, 0);
+    {
+        let mut dummy = TestAccount::new(Pubkey::new_unique(), Pubkey::default(), 0, vec![]);
+        let accs = vec![
+            f.admin.to_info(),
+            f.slab.to_info(),
+            f.mint.to_info(),
+            f.vault.to_info(),
+            f.token_prog.to_info(),
+            f.clock.to_info(),
+            f.rent.to_info(),
+            dummy.to_info(),
+            f.system.to_info(),
+        ];
+        process_instruction(&f.program_id, &accs, &init_data).unwrap();
+    }
+
+    let mut user = TestAccount::new(
+        Pubkey::new_unique(),
+        solana_program::system_program::id(),
+        0,
+        vec![],
+    )
+    .signer();
+    let mut user_ata = TestAccount::new(
+        Pubkey::new_unique(),
+        spl_token::ID,
+        0,
+        make_token_account(f.mint.key, user.key, 1000),
+    )
+    .writable();
+    {
+        let accounts = vec![
+            user.to_info(),
+            f.slab.to_info(),
+            user_ata.to_info(),
+            f.vault.to_info(),
+            f.token_prog.to_info(),
+        ];
+        process_instruction(&f.program_id, &accounts, &encode_init_user(0)).unwrap();
+    }
+    let user_idx = find_idx_by_owner(&f.slab.data, user.key).unwrap();
+
+    {
+        let accounts = vec![
+            user.to_info(),
+            f.slab.to_info(),
+            user_ata.to_info(),
+            f.vault.to_info(),
+            f.token_prog.to_info(),
+            f.clock.to_info(),
+        ];
+        process_instruction(&f.program_id, &accounts, &encode_deposit(user_idx, 500)).unwrap();
     }
 
     {
@@ -3233,3 +3932,1133 @@ fn test_close_slab_non_admin_rejected() {
         "Slab should still be initialized after failed close"
     );
 }
+
+#[test]
+#[cfg(feature = "test")]
+fn test_withdraw_blocked_by_min_insurance_ratio() {
+    let mut f = setup_market();
+    let init_data = encode_init_market(&f, 100);
+    {
+        let mut dummy_ata = TestAccount::new(Pubkey::new_unique(), Pubkey::default(), 0, vec![]);
+        let accounts = vec![
+            f.admin.to_info(),
+            f.slab.to_info(),
+            f.mint.to_info(),
+            f.vault.to_info(),
+            f.token_prog.to_info(),
+            f.clock.to_info(),
+            f.rent.to_info(),
+            dummy_ata.to_info(),
+            f.system.to_info(),
+        ];
+        process_instruction(&f.program_id, &accounts, &init_data).unwrap();
+    }
+
+    let mut user = TestAccount::new(
+        Pubkey::new_unique(),
+        solana_program::system_program::id(),
+        0,
+        vec![],
+    )
+    .signer();
+    let mut user_ata = TestAccount::new(
+        Pubkey::new_unique(),
+        spl_token::ID,
+        0,
+        make_token_account(f.mint.key, user.key, 1000),
+    )
+    .writable();
+    {
+        let accounts = vec![
+            user.to_info(),
+            f.slab.to_info(),
+            user_ata.to_info(),
+            f.vault.to_info(),
+            f.token_prog.to_info(),
+        ];
+        process_instruction(&f.program_id, &accounts, &encode_init_user(0)).unwrap();
+    }
+    let user_idx = find_idx_by_owner(&f.slab.data, user.key).unwrap();
+    {
+        let accounts = vec![
+            user.to_info(),
+            f.slab.to_info(),
+            user_ata.to_info(),
+            f.vault.to_info(),
+            f.token_prog.to_info(),
+            f.clock.to_info(),
+        ];
+        process_instruction(&f.program_id, &accounts, &encode_deposit(user_idx, 1000)).unwrap();
+    }
+
+    let mut lp = TestAccount::new(
+        Pubkey::new_unique(),
+        solana_program::system_program::id(),
+        0,
+        vec![],
+    )
+    .signer();
+    let mut lp_ata = TestAccount::new(
+        Pubkey::new_unique(),
+        spl_token::ID,
+        0,
+        make_token_account(f.mint.key, lp.key, 1500),
+    )
+    .writable();
+    {
+        let accs = vec![
+            lp.to_info(),
+            f.slab.to_info(),
+            lp_ata.to_info(),
+            f.vault.to_info(),
+            f.token_prog.to_info(),
+        ];
+        process_instruction(
+            &f.program_id,
+            &accs,
+            &encode_init_lp(Pubkey::new_unique(), Pubkey::new_unique(), 0),
+        )
+        .unwrap();
+    }
+    let lp_idx = find_idx_by_owner(&f.slab.data, lp.key).unwrap();
+    {
+        let accounts = vec![
+            lp.to_info(),
+            f.slab.to_info(),
+            lp_ata.to_info(),
+            f.vault.to_info(),
+            f.token_prog.to_info(),
+            f.clock.to_info(),
+        ];
+        process_instruction(&f.program_id, &accounts, &encode_deposit(lp_idx, 1000)).unwrap();
+    }
+
+    // Open interest with an empty insurance fund
+    {
+        let accounts = vec![
+            user.to_info(),
+            lp.to_info(),
+            f.slab.to_info(),
+            f.clock.to_info(),
+            f.pyth_index.to_info(),
+        ];
+        process_instruction(
+            &f.program_id,
+            &accounts,
+            &encode_trade(lp_idx, user_idx, 100),
+        )
+        .unwrap();
+    }
+
+    // Admin requires insurance >= 1% of open interest
+    {
+        let accs = vec![f.admin.to_info(), f.slab.to_info()];
+        process_instruction(&f.program_id, &accs, &encode_set_min_insurance_ratio(100)).unwrap();
+    }
+    assert_eq!(
+        state::read_config(&f.slab.data).min_insurance_to_oi_bps,
+        100
+    );
+
+    let mut vault_pda_account =
+        TestAccount::new(f.vault_pda, solana_program::system_program::id(), 0, vec![]);
+    {
+        let accounts = vec![
+            user.to_info(),
+            f.slab.to_info(),
+            f.vault.to_info(),
+            user_ata.to_info(),
+            vault_pda_account.to_info(),
+            f.token_prog.to_info(),
+            f.clock.to_info(),
+            f.pyth_index.to_info(),
+        ];
+        let res = process_instruction(&f.program_id, &accounts, &encode_withdraw(user_idx, 100));
+        assert_eq!(res, Err(PercolatorError::InsuranceRatioTooLow.into()));
+    }
+
+    // Topping up insurance lifts the block
+    {
+        let accs = vec![
+            lp.to_info(),
+            f.slab.to_info(),
+            lp_ata.to_info(),
+            f.vault.to_info(),
+            f.token_prog.to_info(),
+        ];
+        process_instruction(&f.program_id, &accs, &encode_topup_insurance(500)).unwrap();
+    }
+    {
+        let accounts = vec![
+            user.to_info(),
+            f.slab.to_info(),
+            f.vault.to_info(),
+            user_ata.to_info(),
+            vault_pda_account.to_info(),
+            f.token_prog.to_info(),
+            f.clock.to_info(),
+            f.pyth_index.to_info(),
+        ];
+        process_instruction(&f.program_id, &accounts, &encode_withdraw(user_idx, 100)).unwrap();
+    }
+}
+
+#[test]
+fn test_sweep_orphaned_vault_to_insurance() {
+    let mut f = setup_market();
+    let init_data = encode_init_market(&f, 100);
+    {
+        let mut dummy_ata = TestAccount::new(Pubkey::new_unique(), Pubkey::default(), 0, vec![]);
+        let accounts = vec![
+            f.admin.to_info(),
+            f.slab.to_info(),
+            f.mint.to_info(),
+            f.vault.to_info(),
+            f.token_prog.to_info(),
+            f.clock.to_info(),
+            f.rent.to_info(),
+            dummy_ata.to_info(),
+            f.system.to_info(),
+        ];
+        process_instruction(&f.program_id, &accounts, &init_data).unwrap();
+    }
+
+    // Simulate drift: vault credited without any account/insurance credit
+    {
+        let engine = zc::engine_mut(&mut f.slab.data).unwrap();
+        engine.vault = U128::new(engine.vault.get() + 500);
+        assert_eq!(percolator_prog::orphaned_vault(engine), 500);
+    }
+
+    // Non-admin cannot sweep
+    let mut attacker = TestAccount::new(
+        Pubkey::new_unique(),
+        solana_program::system_program::id(),
+        0,
+        vec![],
+    )
+    .signer();
+    {
+        let accs = vec![attacker.to_info(), f.slab.to_info()];
+        let res = process_instruction(&f.program_id, &accs, &encode_sweep_orphaned_vault());
+        assert_eq!(res, Err(PercolatorError::EngineUnauthorized.into()));
+    }
+
+    {
+        let accs = vec![f.admin.to_info(), f.slab.to_info()];
+        process_instruction(&f.program_id, &accs, &encode_sweep_orphaned_vault()).unwrap();
+    }
+
+    let engine = zc::engine_ref(&f.slab.data).unwrap();
+    assert_eq!(percolator_prog::orphaned_vault(engine), 0);
+    assert_eq!(engine.insurance_fund.balance.get(), 500);
+}
+
+#[test]
+fn test_haircut_on_pnl_preview() {
+    let mut f = setup_market();
+    let init_data = encode_init_market(&f, 100);
+    {
+        let mut dummy_ata = TestAccount::new(Pubkey::new_unique(), Pubkey::default(), 0, vec![]);
+        let accounts = vec![
+            f.admin.to_info(),
+            f.slab.to_info(),
+            f.mint.to_info(),
+            f.vault.to_info(),
+            f.token_prog.to_info(),
+            f.clock.to_info(),
+            f.rent.to_info(),
+            dummy_ata.to_info(),
+            f.system.to_info(),
+        ];
+        process_instruction(&f.program_id, &accounts, &init_data).unwrap();
+    }
+
+    let mut user = TestAccount::new(
+        Pubkey::new_unique(),
+        solana_program::system_program::id(),
+        0,
+        vec![],
+    )
+    .signer();
+    let mut user_ata = TestAccount::new(
+        Pubkey::new_unique(),
+        spl_token::ID,
+        0,
+        make_token_account(f.mint.key, user.key, 1000),
+    )
+    .writable();
+    {
+        let accounts = vec![
+            user.to_info(),
+            f.slab.to_info(),
+            user_ata.to_info(),
+            f.vault.to_info(),
+            f.token_prog.to_info(),
+        ];
+        process_instruction(&f.program_id, &accounts, &encode_init_user(0)).unwrap();
+    }
+    let user_idx = find_idx_by_owner(&f.slab.data, user.key).unwrap();
+
+    // Fully backed (no positive PnL outstanding): no haircut
+    {
+        let engine = zc::engine_ref(&f.slab.data).unwrap();
+        assert_eq!(percolator_prog::haircut_on_pnl(engine, 1_000), 0);
+    }
+
+    // Unbacked positive PnL: preview equals x - effective_pos_pnl(x)
+    let engine = zc::engine_mut(&mut f.slab.data).unwrap();
+    engine.set_pnl(user_idx as usize, 1_000);
+    let haircut = percolator_prog::haircut_on_pnl(engine, 1_000);
+    assert_eq!(haircut, 1_000 - engine.effective_pos_pnl(1_000));
+    assert!(haircut > 0, "unbacked profit must be haircut");
+}
+
+#[test]
+fn test_total_user_claims_counts_only_warmed_pnl() {
+    let TradedMarket {
+        mut f, user_idx, ..
+    } = setup_traded_market();
+
+    // Profit of 1_000 warming at 100 per slot from slot 100
+    let engine = zc::engine_mut(&mut f.slab.data).unwrap();
+    let capital = engine.c_tot.get();
+    engine.set_pnl(user_idx as usize, 1_000);
+    engine.accounts[user_idx as usize].warmup_slope_per_step = U128::new(100);
+    engine.accounts[user_idx as usize].warmup_started_at_slot = 100;
+
+    // Nothing warmed yet: claims are capital only
+    assert_eq!(percolator_prog::total_user_claims(engine, 100), capital);
+
+    // Half warmed: only the warmed 500 counts, after haircut
+    assert_eq!(
+        percolator_prog::total_user_claims(engine, 105),
+        capital + engine.effective_pos_pnl(500)
+    );
+
+    // Fully warmed: capped at the available PnL
+    let full = percolator_prog::total_user_claims(engine, 1_000);
+    assert_eq!(full, capital + engine.effective_pos_pnl(1_000));
+    assert!(full < capital + 1_000, "unbacked profit must be haircut");
+
+    // Haircut keeps user claims within what the vault can pay
+    assert!(full <= engine.vault.get() - engine.insurance_fund.balance.get());
+}
+
+#[test]
+fn test_set_margin_params() {
+    let TradedMarket {
+        mut f, user_idx, ..
+    } = setup_traded_market();
+
+    // initial < maintenance is rejected
+    {
+        let accs = vec![f.admin.to_info(), f.slab.to_info()];
+        let res = process_instruction(&f.program_id, &accs, &encode_set_margin_params(500, 1000));
+        assert_eq!(res, Err(PercolatorError::InvalidConfigParam.into()));
+    }
+
+    // A zero maintenance margin would disable liquidation
+    for (initial, maint) in [(500, 0), (0, 0)] {
+        let accs = vec![f.admin.to_info(), f.slab.to_info()];
+        let res = process_instruction(
+            &f.program_id,
+            &accs,
+            &encode_set_margin_params(initial, maint),
+        );
+        assert_eq!(res, Err(PercolatorError::InvalidConfigParam.into()));
+    }
+
+    // User holds 100 @ $100 (notional 10_000) with equity 1000; a 15%
+    // maintenance margin requires 1500, so the user falls below it.
+    {
+        let accs = vec![f.admin.to_info(), f.slab.to_info()];
+        process_instruction(&f.program_id, &accs, &encode_set_margin_params(2000, 1500)).unwrap();
+    }
+
+    let engine = zc::engine_ref(&f.slab.data).unwrap();
+    assert_eq!(engine.params.initial_margin_bps, 2000);
+    assert_eq!(engine.params.maintenance_margin_bps, 1500);
+
+    let mut out = [0u16; 4];
+    let n = percolator_prog::accounts_below_maintenance(engine, 100_000_000, &mut out);
+    assert!(out[..n].contains(&user_idx));
+}
+
+#[test]
+fn test_set_strict_withdraw_and_honor_check() {
+    let mut f = setup_market();
+    let init_data = encode_init_market(&f, 100);
+    {
+        let mut dummy_ata = TestAccount::new(Pubkey::new_unique(), Pubkey::default(), 0, vec![]);
+        let accounts = vec![
+            f.admin.to_info(),
+            f.slab.to_info(),
+            f.mint.to_info(),
+            f.vault.to_info(),
+            f.token_prog.to_info(),
+            f.clock.to_info(),
+            f.rent.to_info(),
+            dummy_ata.to_info(),
+            f.system.to_info(),
+        ];
+        process_instruction(&f.program_id, &accounts, &init_data).unwrap();
+    }
+
+    assert!(!state::is_strict_withdraw(&f.slab.data));
+    {
+        let accs = vec![f.admin.to_info(), f.slab.to_info()];
+        process_instruction(&f.program_id, &accs, &encode_set_strict_withdraw(1)).unwrap();
+    }
+    assert!(state::is_strict_withdraw(&f.slab.data));
+    assert!(!state::is_resolved(&f.slab.data));
+
+    {
+        let accs = vec![f.admin.to_info(), f.slab.to_info()];
+        process_instruction(&f.program_id, &accs, &encode_set_strict_withdraw(0)).unwrap();
+    }
+    assert!(!state::is_strict_withdraw(&f.slab.data));
+
+    // Unused slot can never honor a withdrawal
+    let engine = zc::engine_ref(&f.slab.data).unwrap();
+    assert!(!percolator_prog::system_can_honor_withdrawal(engine, 0, 1));
+}
+
+#[test]
+#[cfg(feature = "test")]
+fn test_strict_withdraw_blocks_what_lax_allows() {
+    let mut f = setup_market();
+    let init_data = encode_init_market(&f, 100);
+    {
+        let mut dummy_ata = TestAccount::new(Pubkey::new_unique(), Pubkey::default(), 0, vec![]);
+        let accounts = vec![
+            f.admin.to_info(),
+            f.slab.to_info(),
+            f.mint.to_info(),
+            f.vault.to_info(),
+            f.token_prog.to_info(),
+            f.clock.to_info(),
+            f.rent.to_info(),
+            dummy_ata.to_info(),
+            f.system.to_info(),
+        ];
+        process_instruction(&f.program_id, &accounts, &init_data).unwrap();
+    }
+
+    let mut user = TestAccount::new(
+        Pubkey::new_unique(),
+        solana_program::system_program::id(),
+        0,
+        vec![],
+    )
+    .signer();
+    let mut user_ata = TestAccount::new(
+        Pubkey::new_unique(),
+        spl_token::ID,
+        0,
+        make_token_account(f.mint.key, user.key, 1000),
+    )
+    .writable();
+    {
+        let accounts = vec![
+            user.to_info(),
+            f.slab.to_info(),
+            user_ata.to_info(),
+            f.vault.to_info(),
+            f.token_prog.to_info(),
+        ];
+        process_instruction(&f.program_id, &accounts, &encode_init_user(0)).unwrap();
+    }
+    let user_idx = find_idx_by_owner(&f.slab.data, user.key).unwrap();
+    {
+        let accounts = vec![
+            user.to_info(),
+            f.slab.to_info(),
+            user_ata.to_info(),
+            f.vault.to_info(),
+            f.token_prog.to_info(),
+            f.clock.to_info(),
+        ];
+        process_instruction(&f.program_id, &accounts, &encode_deposit(user_idx, 1000)).unwrap();
+    }
+
+    // The check is bounded by the amount: the full balance is honorable, one
+    // unit more is not.
+    {
+        let engine = zc::engine_ref(&f.slab.data).unwrap();
+        assert!(percolator_prog::system_can_honor_withdrawal(
+            engine, user_idx, 1000
+        ));
+        assert!(!percolator_prog::system_can_honor_withdrawal(
+            engine, user_idx, 1001
+        ));
+    }
+
+    // Empty insurance fund under a nonzero risk-reduction threshold
+    {
+        let accs = vec![f.admin.to_info(), f.slab.to_info()];
+        process_instruction(&f.program_id, &accs, &encode_set_risk_threshold(1)).unwrap();
+    }
+    {
+        let accs = vec![f.admin.to_info(), f.slab.to_info()];
+        process_instruction(&f.program_id, &accs, &encode_set_strict_withdraw(1)).unwrap();
+    }
+
+    let mut vault_pda_account =
+        TestAccount::new(f.vault_pda, solana_program::system_program::id(), 0, vec![]);
+    {
+        let accounts = vec![
+            user.to_info(),
+            f.slab.to_info(),
+            f.vault.to_info(),
+            user_ata.to_info(),
+            vault_pda_account.to_info(),
+            f.token_prog.to_info(),
+            f.clock.to_info(),
+            f.pyth_index.to_info(),
+        ];
+        let res = process_instruction(&f.program_id, &accounts, &encode_withdraw(user_idx, 100));
+        assert_eq!(res, Err(PercolatorError::WithdrawalNotHonorable.into()));
+    }
+    assert_eq!(TokenAccount::unpack(&f.vault.data).unwrap().amount, 1000);
+
+    // Same withdrawal goes through once strict mode is off
+    {
+        let accs = vec![f.admin.to_info(), f.slab.to_info()];
+        process_instruction(&f.program_id, &accs, &encode_set_strict_withdraw(0)).unwrap();
+    }
+    {
+        let accounts = vec![
+            user.to_info(),
+            f.slab.to_info(),
+            f.vault.to_info(),
+            user_ata.to_info(),
+            vault_pda_account.to_info(),
+            f.token_prog.to_info(),
+            f.clock.to_info(),
+            f.pyth_index.to_info(),
+        ];
+        process_instruction(&f.program_id, &accounts, &encode_withdraw(user_idx, 100)).unwrap();
+    }
+    assert_eq!(TokenAccount::unpack(&f.vault.data).unwrap().amount, 900);
+}
+
+#[test]
+fn test_funding_pause_holds_index_and_resumes_from_now() {
+    let TradedMarket { mut f, lp_idx, .. } = setup_traded_market();
+    assert_ne!(
+        zc::engine_ref(&f.slab.data).unwrap().accounts[lp_idx as usize]
+            .position_size
+            .get(),
+        0
+    );
+
+    // Tiny inventory scale: the open book pays a nonzero rate every slot
+    let update = encode_update_funding_scale(&f.slab.data, 1);
+    {
+        let accs = vec![f.admin.to_info(), f.slab.to_info()];
+        process_instruction(&f.program_id, &accs, &update).unwrap();
+    }
+
+    let mut keeper = TestAccount::new(
+        Pubkey::new_unique(),
+        solana_program::system_program::id(),
+        0,
+        vec![],
+    );
+    let mut crank_at = |f: &mut MarketFixture, slot: u64| {
+        f.clock.data = make_clock(slot, slot as i64);
+        {
+            let accs = vec![
+                keeper.to_info(),
+                f.slab.to_info(),
+                f.clock.to_info(),
+                f.pyth_index.to_info(),
+            ];
+            process_instruction(&f.program_id, &accs, &encode_crank_permissionless(0)).unwrap();
+        }
+        zc::engine_ref(&f.slab.data).unwrap().funding_index_qpb_e6
+    };
+
+    let first = crank_at(&mut f, 101);
+    let accrued = crank_at(&mut f, 110);
+    assert_ne!(accrued, first, "rate must be nonzero while unpaused");
+
+    // Pausing with funding not yet accrued to this slot is rejected
+    f.clock.data = make_clock(120, 120);
+    {
+        let accs = vec![f.admin.to_info(), f.slab.to_info(), f.clock.to_info()];
+        let res = process_instruction(&f.program_id, &accs, &encode_set_funding_paused(1));
+        assert_eq!(res, Err(PercolatorError::CrankStale.into()));
+    }
+    assert!(!state::is_funding_paused(&f.slab.data));
+
+    // Crank, then pause in the same slot
+    let at_pause = crank_at(&mut f, 120);
+    assert_ne!(at_pause, accrued);
+    {
+        let accs = vec![f.admin.to_info(), f.slab.to_info(), f.clock.to_info()];
+        process_instruction(&f.program_id, &accs, &encode_set_funding_paused(1)).unwrap();
+    }
+    assert!(state::is_funding_paused(&f.slab.data));
+
+    // Index is frozen across paused cranks while last_funding_slot advances
+    assert_eq!(crank_at(&mut f, 130), at_pause);
+    assert_eq!(crank_at(&mut f, 140), at_pause);
+    assert_eq!(zc::engine_ref(&f.slab.data).unwrap().last_funding_slot, 140);
+
+    // Resume at a later slot: accrual restarts from now (no catch-up window)
+    f.clock.data = make_clock(150, 150);
+    {
+        let accs = vec![f.admin.to_info(), f.slab.to_info(), f.clock.to_info()];
+        process_instruction(&f.program_id, &accs, &encode_set_funding_paused(0)).unwrap();
+    }
+    assert!(!state::is_funding_paused(&f.slab.data));
+    assert_eq!(zc::engine_ref(&f.slab.data).unwrap().last_funding_slot, 150);
+
+    assert_ne!(crank_at(&mut f, 160), at_pause);
+}
+
+#[test]
+fn test_find_by_account_id_round_trip() {
+    let mut f = setup_market();
+    let init_data = encode_init_market(&f, 100);
+    {
+        let mut dummy_ata = TestAccount::new(Pubkey::new_unique(), Pubkey::default(), 0, vec![]);
+        let accounts = vec![
+            f.admin.to_info(),
+            f.slab.to_info(),
+            f.mint.to_info(),
+            f.vault.to_info(),
+            f.token_prog.to_info(),
+            f.clock.to_info(),
+            f.rent.to_info(),
+            dummy_ata.to_info(),
+            f.system.to_info(),
+        ];
+        process_instruction(&f.program_id, &accounts, &init_data).unwrap();
+    }
+
+    let mut idxs = Vec::new();
+    for _ in 0..2 {
+        let mut user = TestAccount::new(
+            Pubkey::new_unique(),
+            solana_program::system_program::id(),
+            0,
+            vec![],
+        )
+        .signer();
+        let mut user_ata = TestAccount::new(
+            Pubkey::new_unique(),
+            spl_token::ID,
+            0,
+            make_token_account(f.mint.key, user.key, 1000),
+        )
+        .writable();
+        let accounts = vec![
+            user.to_info(),
+            f.slab.to_info(),
+            user_ata.to_info(),
+            f.vault.to_info(),
+            f.token_prog.to_info(),
+        ];
+        process_instruction(&f.program_id, &accounts, &encode_init_user(0)).unwrap();
+        idxs.push(find_idx_by_owner(&f.slab.data, user.key).unwrap());
+    }
+
+    let engine = zc::engine_ref(&f.slab.data).unwrap();
+    for &i in &idxs {
+        let id = engine.accounts[i as usize].account_id;
+        assert_eq!(percolator_prog::find_by_account_id(engine, id), Some(i));
+    }
+    assert_eq!(percolator_prog::find_by_account_id(engine, u64::MAX), None);
+}
+
+#[test]
+fn test_fee_debt_accrues_once_capital_is_drained() {
+    let TradedMarket {
+        mut f,
+        mut user,
+        user_idx,
+        ..
+    } = setup_traded_market();
+    {
+        let engine = zc::engine_ref(&f.slab.data).unwrap();
+        assert_eq!(percolator_prog::total_fee_debt(engine), 0);
+        assert_eq!(percolator_prog::total_fee_credit(engine), 0);
+    }
+
+    // 20 per slot for 100+ slots outruns the user's 1_000 of capital; the
+    // long stays in profit at $105 so it is not closed out on the way.
+    {
+        let accs = vec![f.admin.to_info(), f.slab.to_info()];
+        process_instruction(&f.program_id, &accs, &encode_set_maintenance_fee(20)).unwrap();
+    }
+    f.pyth_index.data = make_pyth(&TEST_FEED_ID, 105_000_000, -6, 1, 200);
+    f.clock.data = make_clock(200, 200);
+    {
+        let accs = vec![
+            user.to_info(),
+            f.slab.to_info(),
+            f.clock.to_info(),
+            f.pyth_index.to_info(),
+        ];
+        process_instruction(&f.program_id, &accs, &encode_crank(user_idx, 0)).unwrap();
+    }
+
+    let engine = zc::engine_ref(&f.slab.data).unwrap();
+    let acc = &engine.accounts[user_idx as usize];
+    assert_eq!(acc.capital.get(), 0);
+    let owed = acc.fee_credits.get();
+    assert!(owed < 0, "unpaid fees must show up as debt");
+    assert!(percolator_prog::total_fee_debt(engine) >= owed.unsigned_abs());
+    assert_eq!(percolator_prog::total_fee_credit(engine), 0);
+}
+
+#[test]
+#[cfg(feature = "test")]
+fn test_contribute_insurance_moves_capital() {
+    let mut f = setup_market();
+    let init_data = encode_init_market(&f, 100);
+    {
+        let mut dummy_ata = TestAccount::new(Pubkey::new_unique(), Pubkey::default(), 0, vec![]);
+        let accounts = vec![
+            f.admin.to_info(),
+            f.slab.to_info(),
+            f.mint.to_info(),
+            f.vault.to_info(),
+            f.token_prog.to_info(),
+            f.clock.to_info(),
+            f.rent.to_info(),
+            dummy_ata.to_info(),
+            f.system.to_info(),
+        ];
+        process_instruction(&f.program_id, &accounts, &init_data).unwrap();
+    }
+
+    let mut user = TestAccount::new(
+        Pubkey::new_unique(),
+        solana_program::system_program::id(),
+        0,
+        vec![],
+    )
+    .signer();
+    let mut user_ata = TestAccount::new(
+        Pubkey::new_unique(),
+        spl_token::ID,
+        0,
+        make_token_account(f.mint.key, user.key, 1000),
+    )
+    .writable();
+    {
+        let accounts = vec![
+            user.to_info(),
+            f.slab.to_info(),
+            user_ata.to_info(),
+            f.vault.to_info(),
+            f.token_prog.to_info(),
+        ];
+        process_instruction(&f.program_id, &accounts, &encode_init_user(0)).unwrap();
+    }
+    let user_idx = find_idx_by_owner(&f.slab.data, user.key).unwrap();
+    {
+        let accounts = vec![
+            user.to_info(),
+            f.slab.to_info(),
+            user_ata.to_info(),
+            f.vault.to_info(),
+            f.token_prog.to_info(),
+            f.clock.to_info(),
+        ];
+        process_instruction(&f.program_id, &accounts, &encode_deposit(user_idx, 1000)).unwrap();
+    }
+
+    let (vault_before, c_tot_before, ins_before) = {
+        let engine = zc::engine_ref(&f.slab.data).unwrap();
+        (
+            engine.vault.get(),
+            engine.c_tot.get(),
+            engine.insurance_fund.balance.get(),
+        )
+    };
+
+    // More than the account's capital is rejected
+    {
+        let accs = vec![
+            user.to_info(),
+            f.slab.to_info(),
+            f.clock.to_info(),
+            f.pyth_index.to_info(),
+        ];
+        let res = process_instruction(
+            &f.program_id,
+            &accs,
+            &encode_contribute_insurance(user_idx, 1001),
+        );
+        assert_eq!(res, Err(PercolatorError::EngineInsufficientBalance.into()));
+    }
+
+    {
+        let accs = vec![
+            user.to_info(),
+            f.slab.to_info(),
+            f.clock.to_info(),
+            f.pyth_index.to_info(),
+        ];
+        process_instruction(
+            &f.program_id,
+            &accs,
+            &encode_contribute_insurance(user_idx, 400),
+        )
+        .unwrap();
+    }
+
+    let engine = zc::engine_ref(&f.slab.data).unwrap();
+    assert_eq!(engine.vault.get(), vault_before);
+    assert_eq!(engine.c_tot.get(), c_tot_before - 400);
+    assert_eq!(engine.insurance_fund.balance.get(), ins_before + 400);
+    assert_eq!(engine.accounts[user_idx as usize].capital.get(), 600);
+}
+
+#[test]
+fn test_contribute_insurance_rejected_below_initial_margin() {
+    let TradedMarket {
+        mut f,
+        mut user,
+        user_idx,
+        ..
+    } = setup_traded_market();
+
+    let ins_before = zc::engine_ref(&f.slab.data)
+        .unwrap()
+        .insurance_fund
+        .balance
+        .get();
+
+    // 10% initial margin on 100 contracts at $100 needs all 1_000 of capital
+    {
+        let accs = vec![f.admin.to_info(), f.slab.to_info()];
+        process_instruction(&f.program_id, &accs, &encode_set_margin_params(1_000, 500)).unwrap();
+    }
+
+    {
+        let accs = vec![
+            user.to_info(),
+            f.slab.to_info(),
+            f.clock.to_info(),
+            f.pyth_index.to_info(),
+        ];
+        let res = process_instruction(
+            &f.program_id,
+            &accs,
+            &encode_contribute_insurance(user_idx, 100),
+        );
+        assert_eq!(res, Err(PercolatorError::EngineUndercollateralized.into()));
+    }
+
+    let engine = zc::engine_ref(&f.slab.data).unwrap();
+    assert_eq!(engine.accounts[user_idx as usize].capital.get(), 1_000);
+    assert_eq!(engine.insurance_fund.balance.get(), ins_before);
+}
+
+#[test]
+#[cfg(feature = "test")]
+fn test_min_deposit_floor() {
+    let mut f = setup_market();
+    let init_data = encode_init_market(&f, 100);
+    {
+        let mut dummy_ata = TestAccount::new(Pubkey::new_unique(), Pubkey::default(), 0, vec![]);
+        let accounts = vec![
+            f.admin.to_info(),
+            f.slab.to_info(),
+            f.mint.to_info(),
+            f.vault.to_info(),
+            f.token_prog.to_info(),
+            f.clock.to_info(),
+            f.rent.to_info(),
+            dummy_ata.to_info(),
+            f.system.to_info(),
+        ];
+        process_instruction(&f.program_id, &accounts, &init_data).unwrap();
+    }
+
+    let mut user = TestAccount::new(
+        Pubkey::new_unique(),
+        solana_program::system_program::id(),
+        0,
+        vec![],
+    )
+    .signer();
+    let mut user_ata = TestAccount::new(
+        Pubkey::new_unique(),
+        spl_token::ID,
+        0,
+        make_token_account(f.mint.key, user.key, 1000),
+    )
+    .writable();
+    {
+        let accounts = vec![
+            user.to_info(),
+            f.slab.to_info(),
+            user_ata.to_info(),
+            f.vault.to_info(),
+            f.token_prog.to_info(),
+        ];
+        process_instruction(&f.program_id, &accounts, &encode_init_user(0)).unwrap();
+    }
+    let user_idx = find_idx_by_owner(&f.slab.data, user.key).unwrap();
+
+    {
+        let accs = vec![f.admin.to_info(), f.slab.to_info()];
+        process_instruction(&f.program_id, &accs, &encode_set_min_deposit(500)).unwrap();
+    }
+
+    // Healthy (flat) account: below-minimum deposit is rejected
+    {
+        let accounts = vec![
+            user.to_info(),
+            f.slab.to_info(),
+            user_ata.to_info(),
+            f.vault.to_info(),
+            f.token_prog.to_info(),
+            f.clock.to_info(),
+        ];
+        let res = process_instruction(&f.program_id, &accounts, &encode_deposit(user_idx, 100));
+        assert_eq!(res, Err(PercolatorError::DepositTooSmall.into()));
+    }
+    {
+        let accounts = vec![
+            user.to_info(),
+            f.slab.to_info(),
+            user_ata.to_info(),
+            f.vault.to_info(),
+            f.token_prog.to_info(),
+            f.clock.to_info(),
+        ];
+        process_instruction(&f.program_id, &accounts, &encode_deposit(user_idx, 500)).unwrap();
+    }
+    assert_eq!(
+        zc::engine_ref(&f.slab.data).unwrap().accounts[user_idx as usize]
+            .capital
+            .get(),
+        500
+    );
+
+    // LP deposits are exempt from the user-only floor
+    let mut lp = TestAccount::new(
+        Pubkey::new_unique(),
+        solana_program::system_program::id(),
+        0,
+        vec![],
+    )
+    .signer();
+    let mut lp_ata = TestAccount::new(
+        Pubkey::new_unique(),
+        spl_token::ID,
+        0,
+        make_token_account(f.mint.key, lp.key, 1000),
+    )
+    .writable();
+    let d1 = TestAccount::new(Pubkey::new_unique(), Pubkey::default(), 0, vec![]);
+    let d2 = TestAccount::new(Pubkey::new_unique(), Pubkey::default(), 0, vec![]);
+    {
+        let accs = vec![
+            lp.to_info(),
+            f.slab.to_info(),
+            lp_ata.to_info(),
+            f.vault.to_info(),
+            f.token_prog.to_info(),
+        ];
+        process_instruction(&f.program_id, &accs, &encode_init_lp(d1.key, d2.key, 0)).unwrap();
+    }
+    let lp_idx = find_idx_by_owner(&f.slab.data, lp.key).unwrap();
+    {
+        let accounts = vec![
+            lp.to_info(),
+            f.slab.to_info(),
+            lp_ata.to_info(),
+            f.vault.to_info(),
+            f.token_prog.to_info(),
+            f.clock.to_info(),
+        ];
+        process_instruction(&f.program_id, &accounts, &encode_deposit(lp_idx, 100)).unwrap();
+    }
+    let engine = zc::engine_ref(&f.slab.data).unwrap();
+    assert_eq!(engine.accounts[lp_idx as usize].capital.get(), 100);
+}
+
+#[test]
+#[cfg(feature = "test")]
+fn test_min_deposit_floor_allows_rescue() {
+    let TradedMarket {
+        mut f,
+        mut user,
+        user_idx,
+        ..
+    } = setup_traded_market();
+
+    // 15% maintenance on 100 contracts at $100 needs 1_500; the user holds 1_000
+    {
+        let accs = vec![f.admin.to_info(), f.slab.to_info()];
+        process_instruction(
+            &f.program_id,
+            &accs,
+            &encode_set_margin_params(2_000, 1_500),
+        )
+        .unwrap();
+    }
+    {
+        let accs = vec![f.admin.to_info(), f.slab.to_info()];
+        process_instruction(&f.program_id, &accs, &encode_set_min_deposit(1_000)).unwrap();
+    }
+
+    let mut user_ata = TestAccount::new(
+        Pubkey::new_unique(),
+        spl_token::ID,
+        0,
+        make_token_account(f.mint.key, user.key, 1000),
+    )
+    .writable();
+
+    // Below the floor and not enough to clear maintenance: rejected
+    {
+        let accounts = vec![
+            user.to_info(),
+            f.slab.to_info(),
+            user_ata.to_info(),
+            f.vault.to_info(),
+            f.token_prog.to_info(),
+            f.clock.to_info(),
+        ];
+        let res = process_instruction(&f.program_id, &accounts, &encode_deposit(user_idx, 100));
+        assert_eq!(res, Err(PercolatorError::DepositTooSmall.into()));
+    }
+
+    // Below the floor but lifts the account back above maintenance: allowed
+    {
+        let accounts = vec![
+            user.to_info(),
+            f.slab.to_info(),
+            user_ata.to_info(),
+            f.vault.to_info(),
+            f.token_prog.to_info(),
+            f.clock.to_info(),
+        ];
+        process_instruction(&f.program_id, &accounts, &encode_deposit(user_idx, 600)).unwrap();
+    }
+    assert_eq!(
+        zc::engine_ref(&f.slab.data).unwrap().accounts[user_idx as usize]
+            .capital
+            .get(),
+        1_600
+    );
+}
+
+#[test]
+#[cfg(feature = "test")]
+fn test_max_accounts_per_owner() {
+    let mut f = setup_market();
+    let init_data = encode_init_market(&f, 100);
+    {
+        let mut dummy_ata = TestAccount::new(Pubkey::new_unique(), Pubkey::default(), 0, vec![]);
+        let accounts = vec![
+            f.admin.to_info(),
+            f.slab.to_info(),
+            f.mint.to_info(),
+            f.vault.to_info(),
+            f.token_prog.to_info(),
+            f.clock.to_info(),
+            f.rent.to_info(),
+            dummy_ata.to_info(),
+            f.system.to_info(),
+        ];
+        process_instruction(&f.program_id, &accounts, &init_data).unwrap();
+    }
+    {
+        let accs = vec![f.admin.to_info(), f.slab.to_info()];
+        process_instruction(&f.program_id, &accs, &encode_set_max_accounts_per_owner(1)).unwrap();
+    }
+
+    let mut user = TestAccount::new(
+        Pubkey::new_unique(),
+        solana_program::system_program::id(),
+        0,
+        vec![],
+    )
+    .signer();
+    let mut user_ata = TestAccount::new(
+        Pubkey::new_unique(),
+        spl_token::ID,
+        0,
+        make_token_account(f.mint.key, user.key, 1000),
+    )
+    .writable();
+    {
+        let accounts = vec![
+            user.to_info(),
+            f.slab.to_info(),
+            user_ata.to_info(),
+            f.vault.to_info(),
+            f.token_prog.to_info(),
+        ];
+        process_instruction(&f.program_id, &accounts, &encode_init_user(0)).unwrap();
+    }
+    {
+        let accounts = vec![
+            user.to_info(),
+            f.slab.to_info(),
+            user_ata.to_info(),
+            f.vault.to_info(),
+            f.token_prog.to_info(),
+        ];
+        let res = process_instruction(&f.program_id, &accounts, &encode_init_user(0));
+        assert_eq!(res, Err(PercolatorError::OwnerAccountLimit.into()));
+    }
+    {
+        let engine = zc::engine_ref(&f.slab.data).unwrap();
+        assert_eq!(
+            percolator_prog::owner_account_count(engine, &user.key.to_bytes()),
+            1
+        );
+    }
+
+    // Closing the account frees the owner's slot
+    let user_idx = find_idx_by_owner(&f.slab.data, user.key).unwrap();
+    let mut vault_pda_account =
+        TestAccount::new(f.vault_pda, solana_program::system_program::id(), 0, vec![]);
+    {
+        let accounts = vec![
+            user.to_info(),
+            f.slab.to_info(),
+            f.vault.to_info(),
+            user_ata.to_info(),
+            vault_pda_account.to_info(),
+            f.token_prog.to_info(),
+            f.clock.to_info(),
+            f.pyth_index.to_info(),
+        ];
+        process_instruction(&f.program_id, &accounts, &encode_close_account(user_idx)).unwrap();
+    }
+    assert_eq!(
+        percolator_prog::owner_account_count(
+            zc::engine_ref(&f.slab.data).unwrap(),
+            &user.key.to_bytes()
+        ),
+        0
+    );
+    {
+        let accounts = vec![
+            user.to_info(),
+            f.slab.to_info(),
+            user_ata.to_info(),
+            f.vault.to_info(),
+            f.token_prog.to_info(),
+        ];
+        process_instruction(&f.program_id, &accounts, &encode_init_user(0)).unwrap();
+    }
+    let engine = zc::engine_ref(&f.slab.data).unwrap();
+    assert_eq!(
+        percolator_prog::owner_account_count(engine, &user.key.to_bytes()),
+        1
+    );
+}